@@ -1,46 +1,99 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
 use std::collections::HashMap;
 
-fn main() -> io::Result<()> {
-    let file_path = "src/input.txt";
+fn main() {
+    let input = common::input::load("src/input.txt");
+    let lists = parse_lists(&input).expect(BAD_INPUT);
+    println!("Part 1: {}", solve_part1(&lists));
+    println!("Part 2: {}", solve_part2(&lists));
+}
 
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+const BAD_INPUT: &str = "Every line must be two whole numbers";
 
+type Lists = (Vec<i32>, Vec<i32>);
+
+// None when a line isnt a pair of numbers, those used to be skipped without a word
+fn parse_lists(input: &str) -> Option<Lists> {
     let mut left_list = Vec::new();
     let mut right_list = Vec::new();
 
-    for line in reader.lines() {
-        let line = line?; // Unwrap the line
+    for line in input.lines() {
         let mut parts = line.split_whitespace(); // Split line into parts
-        if let (Some(left), Some(right)) = (parts.next(), parts.next()) {
-            if let (Ok(left_val), Ok(right_val)) = (left.parse::<i32>(), right.parse::<i32>()) {
-                left_list.push(left_val);
-                right_list.push(right_val);
-            }
-        }
+        let (Some(left), Some(right), None) = (parts.next(), parts.next(), parts.next()) else {
+            return None;
+        };
+        left_list.push(left.parse().ok()?);
+        right_list.push(right.parse().ok()?);
     }
 
+    Some((left_list, right_list))
+}
+
+// summed in i64, the distance between two i32s doesnt fit in one
+fn solve_part1((left_list, right_list): &Lists) -> i64 {
+    let mut left_list = left_list.clone();
+    let mut right_list = right_list.clone();
+    left_list.sort();
+    right_list.sort();
+
+    left_list
+        .iter()
+        .zip(right_list.iter())
+        .map(|(&l, &r)| (l as i64 - r as i64).abs())
+        .sum()
+}
+
+fn solve_part2((left_list, right_list): &Lists) -> i64 {
     let mut right_count = HashMap::new();
-    for num in &right_list {
+    for num in right_list {
         *right_count.entry(num).or_insert(0) += 1;
     }
 
-    let similarity_score: i32 = left_list.iter().map(|num| num * right_count.get(num).unwrap_or(&0)).sum();
+    left_list
+        .iter()
+        .map(|&num| num as i64 * right_count.get(&num).unwrap_or(&0))
+        .sum()
+}
 
-    println!("Similarity Score: {}", similarity_score);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    left_list.sort();
-    right_list.sort();
+    const EXAMPLE: &str = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
 
-    let total_distance: i32 = left_list
-        .iter()
-        .zip(right_list.iter())
-        .map(|(l, r)| (l - r).abs())
-        .sum();
+    #[test]
+    fn example() {
+        let lists = parse_lists(EXAMPLE).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&lists), 11);
+        assert_eq!(solve_part2(&lists), 31);
+    }
+
+    #[test]
+    fn odd_lines_and_big_numbers() {
+        assert!(parse_lists("1 2\n3\n").is_none());
+        assert!(parse_lists("1 2 3\n").is_none());
+        assert!(parse_lists("1 x\n").is_none());
 
-    println!("Total Distance: {}", total_distance);
+        // the i32 distance and product both overflowed
+        let lists = parse_lists("2000000000 -2000000000\n2000000000 2000000000\n").unwrap();
+        assert_eq!(solve_part1(&lists), 4_000_000_000);
+        assert_eq!(solve_part2(&lists), 4_000_000_000);
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(lists) = parse_lists(input) {
+                solve_part1(&lists);
+                solve_part2(&lists);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let lists = parse_lists(&input).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&lists), 2970687);
+        assert_eq!(solve_part2(&lists), 23963899);
+    }
+}
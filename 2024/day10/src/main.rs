@@ -1,11 +1,10 @@
 use std::collections::{HashSet, VecDeque};
-use std::fs;
 
 type Pos = (usize, usize);
 type Grid = Vec<Vec<u32>>;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
@@ -42,15 +41,14 @@ fn find_trailheads(grid: &Grid) -> Vec<Pos> {
 
 fn neighbors(pos: Pos, grid: &Grid) -> impl Iterator<Item = Pos> + '_ {
     let (r, c) = pos;
-    let rows = grid.len();
-    let cols = grid[0].len();
 
+    // rows can be ragged, so each one is checked on its own
     [(0, 1), (1, 0), (0, -1), (-1, 0)]
         .into_iter()
         .filter_map(move |(dr, dc)| {
             let nr = r.checked_add_signed(dr)?;
             let nc = c.checked_add_signed(dc)?;
-            if nr < rows && nc < cols {
+            if nc < grid.get(nr)?.len() {
                 Some((nr, nc))
             } else {
                 None
@@ -130,4 +128,48 @@ fn count_paths(pos: Pos, grid: &Grid) -> usize {
         })
         .map(|next_pos| count_paths(next_pos, grid))
         .sum()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 36);
+        assert_eq!(solve_part2(EXAMPLE), 81);
+    }
+
+    #[test]
+    fn ragged_rows() {
+        // crash found by fuzzing, the width of every row came from the first one
+        assert_eq!(solve_part1("0123456789\n0\n"), 1);
+        assert_eq!(solve_part2("01\n.23456789\n"), 1);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 5_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 737);
+        assert_eq!(solve_part2(&input), 1619);
+    }
+}
@@ -1,29 +1,35 @@
 use std::collections::HashMap;
-use std::fs;
+
+use common::num::{self, Arith, Count};
+
+const BAD_STONES: &str = "Expected whole numbers separated by spaces";
+
+fn parse_stones(input: &str) -> Option<Vec<Count>> {
+    input.split_whitespace().map(|s| s.parse().ok()).collect()
+}
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let stones: Vec<u64> = input
-        .trim()
-        .split_whitespace()
-        .map(|s| s.parse().unwrap())
-        .collect();
-
-    let part1 = solve(&stones, 25);
-    let part2 = solve(&stones, 75);
+    let input = common::input::load("src/input.txt");
+    let stones = parse_stones(&input).expect(BAD_STONES);
+
+    let part1 = num::checked_or_exit(|| solve(&stones, 25));
+    let part2 = num::checked_or_exit(|| solve(&stones, 75));
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
 
-fn solve(stones: &[u64], blinks: usize) -> usize {
+fn solve(stones: &[Count], blinks: usize) -> Count {
     let mut cache = HashMap::new();
-    stones
-        .iter()
-        .map(|&stone| count_stones(stone, blinks, &mut cache))
-        .sum()
+    num::sum(
+        stones
+            .iter()
+            .map(|&stone| count_stones(stone, blinks, &mut cache)),
+    )
 }
 
-fn count_stones(stone: u64, blinks_remaining: usize, cache: &mut HashMap<(u64, usize), usize>) -> usize {
+// the count grows exponentially with blinks, so it goes through common::num,
+// and so do the stones since every odd length one gets multiplied by 2024
+fn count_stones(stone: Count, blinks_remaining: usize, cache: &mut HashMap<(Count, usize), Count>) -> Count {
     // Base case: no more blinks
     if blinks_remaining == 0 {
         return 1;
@@ -42,21 +48,79 @@ fn count_stones(stone: u64, blinks_remaining: usize, cache: &mut HashMap<(u64, u
         let digits = stone.to_string();
         let len = digits.len();
 
-        if len % 2 == 0 {
+        if len.is_multiple_of(2) {
             // Rule 2: Even number of digits - split in half
             let mid = len / 2;
-            let left = digits[..mid].parse::<u64>().unwrap();
-            let right = digits[mid..].parse::<u64>().unwrap();
+            let left = digits[..mid].parse::<Count>().unwrap();
+            let right = digits[mid..].parse::<Count>().unwrap();
 
             count_stones(left, blinks_remaining - 1, cache)
-                + count_stones(right, blinks_remaining - 1, cache)
+                .plus(count_stones(right, blinks_remaining - 1, cache))
         } else {
             // Rule 3: Multiply by 2024
-            count_stones(stone * 2024, blinks_remaining - 1, cache)
+            count_stones(stone.times(2024), blinks_remaining - 1, cache)
         }
     };
 
     // Cache the result
     cache.insert((stone, blinks_remaining), result);
     result
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let stones = parse_stones("125 17").expect(BAD_STONES);
+        assert_eq!(solve(&stones, 6), 22);
+        assert_eq!(solve(&stones, 25), 55312);
+    }
+
+    #[cfg(all(feature = "checked", not(feature = "wide")))]
+    #[test]
+    fn overflow_is_an_error() {
+        // 19 digits is odd so the stone gets multiplied by 2024, which doesnt fit in a u64
+        let stones = parse_stones("9999999999999999999").expect(BAD_STONES);
+        let overflow = num::checked(|| solve(&stones, 1)).unwrap_err();
+        assert_eq!(
+            overflow.to_string(),
+            "arithmetic overflow: 9999999999999999999 * 2024 does not fit in u64 (try --features wide)"
+        );
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide_stones_dont_overflow() {
+        // times 2024 twice over, then 26 digits splits in two
+        let stones = parse_stones("9999999999999999999").expect(BAD_STONES);
+        assert_eq!(solve(&stones, 3), 2);
+    }
+
+    #[test]
+    fn stray_text() {
+        // crashes found by fuzzing, anything that wasnt a number was unwrapped
+        assert_eq!(parse_stones("-"), None);
+        assert_eq!(parse_stones("125 é"), None);
+        assert_eq!(parse_stones(" 125\n17\n"), Some(vec![125, 17]));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        // fewer runs than the other days, every one is a whole 25 blink solve
+        common::fuzz::check(&["125 17"], 2_000, |input| {
+            if let Some(stones) = parse_stones(input) {
+                solve(&stones, 25);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let stones = parse_stones(&input).expect(BAD_STONES);
+        assert_eq!(solve(&stones, 25), 235850);
+        assert_eq!(solve(&stones, 75), 279903140844645);
+    }
+}
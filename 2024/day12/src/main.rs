@@ -1,106 +1,84 @@
-use std::collections::{HashSet, VecDeque};
-use std::fs;
+use std::collections::VecDeque;
+
+use common::cells::CellMap;
+use common::geometry::{self, Point};
+use common::render::{Palette, Scene};
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
-    let part1 = solve_part1(&grid);
-    let part2 = solve_part2(&grid);
+    let fences = fences(&grid);
+    let part1 = solve_part1(&fences);
+    let part2 = solve_part2(&fences);
 
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
-}
 
-fn solve_part1(grid: &[Vec<char>]) -> usize {
-    let mut visited = HashSet::new();
-    let mut total_price = 0;
-
-    for row in 0..grid.len() {
-        for col in 0..grid[0].len() {
-            if !visited.contains(&(row, col)) {
-                let (area, perimeter) = flood_fill_area_perimeter(grid, row, col, &mut visited);
-                total_price += area * perimeter;
-            }
-        }
+    // every plant type gets its own colour so the regions stand out
+    if let Some(path) = common::render::requested()
+        && let Err(e) = Scene::from_chars(&input, &Palette::new()).save(&path, 6)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
-
-    total_price
 }
 
-fn solve_part2(grid: &[Vec<char>]) -> usize {
-    let mut visited = HashSet::new();
-    let mut total_price = 0;
-
-    for row in 0..grid.len() {
-        for col in 0..grid[0].len() {
-            if !visited.contains(&(row, col)) {
-                let (area, sides) = flood_fill_area_sides(grid, row, col, &mut visited);
-                total_price += area * sides;
-            }
-        }
-    }
-
-    total_price
+// (area, outline) for every region, both parts price the same fences
+fn fences(grid: &[Vec<char>]) -> Vec<(usize, Vec<Vec<Point>>)> {
+    regions(grid)
+        .iter()
+        .map(|region| (region.len(), outline(grid, region)))
+        .collect()
 }
 
-fn flood_fill_area_perimeter(
-    grid: &[Vec<char>],
-    start_row: usize,
-    start_col: usize,
-    visited: &mut HashSet<(usize, usize)>,
-) -> (usize, usize) {
-    let plant_type = grid[start_row][start_col];
-    let mut queue = VecDeque::new();
-    queue.push_back((start_row, start_col));
-    visited.insert((start_row, start_col));
-
-    let mut area = 0;
-    let mut perimeter = 0;
-
-    let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-
-    while let Some((row, col)) = queue.pop_front() {
-        area += 1;
-
-        for (dr, dc) in directions.iter() {
-            let new_row = row as i32 + dr;
-            let new_col = col as i32 + dc;
+fn solve_part1(fences: &[(usize, Vec<Vec<Point>>)]) -> usize {
+    fences
+        .iter()
+        .map(|(area, outline)| {
+            let perimeter: i64 = outline.iter().map(|p| geometry::boundary_points(p)).sum();
+            area * perimeter as usize
+        })
+        .sum()
+}
 
-            if new_row < 0 || new_row >= grid.len() as i32
-                || new_col < 0 || new_col >= grid[0].len() as i32 {
-                perimeter += 1;
-                continue;
-            }
+fn solve_part2(fences: &[(usize, Vec<Vec<Point>>)]) -> usize {
+    // every turn of the outline is where one side ends and the next starts
+    fences
+        .iter()
+        .map(|(area, outline)| area * outline.iter().map(Vec::len).sum::<usize>())
+        .sum()
+}
 
-            let new_row = new_row as usize;
-            let new_col = new_col as usize;
+fn regions(grid: &[Vec<char>]) -> Vec<Vec<Point>> {
+    // rows can be ragged, the widest one sets the size of the map
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let mut visited = CellMap::new(grid.len(), width, false);
+    let mut regions = Vec::new();
 
-            if grid[new_row][new_col] != plant_type {
-                perimeter += 1;
-            } else if !visited.contains(&(new_row, new_col)) {
-                visited.insert((new_row, new_col));
-                queue.push_back((new_row, new_col));
+    for row in 0..grid.len() {
+        for col in 0..grid[row].len() {
+            if !visited.contains((row, col)) {
+                regions.push(flood_fill(grid, row, col, &mut visited));
             }
         }
     }
 
-    (area, perimeter)
+    regions
 }
 
-fn flood_fill_area_sides(
+fn flood_fill(
     grid: &[Vec<char>],
     start_row: usize,
     start_col: usize,
-    visited: &mut HashSet<(usize, usize)>,
-) -> (usize, usize) {
+    visited: &mut CellMap<bool>,
+) -> Vec<Point> {
     let plant_type = grid[start_row][start_col];
     let mut queue = VecDeque::new();
     queue.push_back((start_row, start_col));
     visited.insert((start_row, start_col));
 
-    let mut region = HashSet::new();
-    region.insert((start_row, start_col));
+    let mut region = vec![(start_row as i64, start_col as i64)];
 
     let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
@@ -109,81 +87,83 @@ fn flood_fill_area_sides(
             let new_row = row as i32 + dr;
             let new_col = col as i32 + dc;
 
-            if new_row < 0 || new_row >= grid.len() as i32
-                || new_col < 0 || new_col >= grid[0].len() as i32 {
+            if new_row < 0 || new_row >= grid.len() as i32 || new_col < 0 {
                 continue;
             }
 
             let new_row = new_row as usize;
             let new_col = new_col as usize;
 
-            if grid[new_row][new_col] == plant_type && !visited.contains(&(new_row, new_col)) {
+            if grid[new_row].get(new_col) == Some(&plant_type)
+                && !visited.contains((new_row, new_col))
+            {
                 visited.insert((new_row, new_col));
-                region.insert((new_row, new_col));
+                region.push((new_row as i64, new_col as i64));
                 queue.push_back((new_row, new_col));
             }
         }
     }
 
-    let area = region.len();
-    let sides = count_corners(&region);
-
-    (area, sides)
+    region
 }
 
-fn count_corners(region: &HashSet<(usize, usize)>) -> usize {
-    let mut corners = 0;
-
-    for &(row, col) in region {
-        // Check all 4 possible corners for this cell
-        // Each corner is determined by checking the adjacent cells in that direction
-
-        // Top left corner
-        let top = region.contains(&(row.wrapping_sub(1), col));
-        let left = region.contains(&(row, col.wrapping_sub(1)));
-        let top_left = region.contains(&(row.wrapping_sub(1), col.wrapping_sub(1)));
-
-        // Outer corner: neither top nor left are in region
-        if !top && !left {
-            corners += 1;
-        }
-        // Inner corner: both top and left are in region, but diagonal is not
-        if top && left && !top_left {
-            corners += 1;
-        }
+fn outline(grid: &[Vec<char>], region: &[Point]) -> Vec<Vec<Point>> {
+    // a neighbour with the same plant is always in the region (the flood fill would have
+    // reached it), so a look at the grid is all the membership check needs
+    let (start_row, start_col) = region[0];
+    let plant_type = grid[start_row as usize][start_col as usize];
+    let in_region = |(row, col): Point| {
+        let cell = usize::try_from(row)
+            .ok()
+            .zip(usize::try_from(col).ok())
+            .and_then(|(row, col)| grid.get(row)?.get(col));
+        cell == Some(&plant_type)
+    };
+
+    geometry::outline(region, in_region)
+}
 
-        // Top right corner
-        let right = region.contains(&(row, col + 1));
-        let top_right = region.contains(&(row.wrapping_sub(1), col + 1));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if !top && !right {
-            corners += 1;
-        }
-        if top && right && !top_right {
-            corners += 1;
-        }
+    fn fenced(input: &str) -> Vec<(usize, Vec<Vec<Point>>)> {
+        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        fences(&grid)
+    }
 
-        // Bottom left corner
-        let bottom = region.contains(&(row + 1, col));
-        let bottom_left = region.contains(&(row + 1, col.wrapping_sub(1)));
+    #[test]
+    fn examples() {
+        let small = fenced("AAAA\nBBCD\nBBCC\nEEEC\n");
+        assert_eq!(solve_part1(&small), 140);
+        assert_eq!(solve_part2(&small), 80);
 
-        if !bottom && !left {
-            corners += 1;
-        }
-        if bottom && left && !bottom_left {
-            corners += 1;
-        }
+        // the two B regions only touch the A region's hole at a corner
+        let pinched = fenced("AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA\n");
+        assert_eq!(solve_part2(&pinched), 368);
+    }
 
-        // Bottom right corner
-        let bottom_right = region.contains(&(row + 1, col + 1));
+    #[test]
+    fn ragged_rows() {
+        // crash found by fuzzing, every row was read as wide as the first
+        assert_eq!(solve_part1(&fenced("A\nAA\n")), 3 * 8);
+        assert_eq!(solve_part2(&fenced("AB\nA\n")), 2 * 4 + 4);
+        assert_eq!(solve_part1(&fenced("")), 0);
+    }
 
-        if !bottom && !right {
-            corners += 1;
-        }
-        if bottom && right && !bottom_right {
-            corners += 1;
-        }
+    #[test]
+    fn fuzz_solve() {
+        let example = "AAAA\nBBCD\nBBCC\nEEEC\n";
+        common::fuzz::check(&[example], 20_000, |input| {
+            solve_part1(&fenced(input));
+            solve_part2(&fenced(input));
+        });
     }
 
-    corners
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&fenced(&input)), 1451030);
+        assert_eq!(solve_part2(&fenced(&input)), 859494);
+    }
 }
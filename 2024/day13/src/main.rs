@@ -1,85 +1,78 @@
-use std::fs;
+use std::cmp::Ordering;
+
+use common::linalg;
+use common::num::{self, Arith, Signed};
 
 #[derive(Debug)]
 struct Machine {
-    a_x: i64,
-    a_y: i64,
-    b_x: i64,
-    b_y: i64,
-    prize_x: i64,
-    prize_y: i64,
+    a_x: Signed,
+    a_y: Signed,
+    b_x: Signed,
+    b_y: Signed,
+    prize_x: Signed,
+    prize_y: Signed,
 }
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let machines = parse_input(&input);
 
-    let part1 = solve_part1(&machines);
-    let part2 = solve_part2(&machines);
+    let part1 = num::checked_or_exit(|| solve_part1(&machines));
+    let part2 = num::checked_or_exit(|| solve_part2(&machines));
 
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
 
 fn parse_input(input: &str) -> Vec<Machine> {
-    let mut machines = Vec::new();
-    let lines: Vec<&str> = input.lines().collect();
-
-    let mut i = 0;
-    while i < lines.len() {
-        if lines[i].is_empty() {
-            i += 1;
-            continue;
-        }
+    // three lines per machine, a machine with a line that doesnt read is skipped
+    let lines: Vec<&str> = input.lines().filter(|line| !line.is_empty()).collect();
 
-        // Parse Button A
-        let a_line = lines[i];
-        let a_parts: Vec<&str> = a_line.split(", ").collect();
-        let a_x = a_parts[0].split("+").nth(1).unwrap().parse::<i64>().unwrap();
-        let a_y = a_parts[1].split("+").nth(1).unwrap().parse::<i64>().unwrap();
-
-        // Parse Button B
-        let b_line = lines[i + 1];
-        let b_parts: Vec<&str> = b_line.split(", ").collect();
-        let b_x = b_parts[0].split("+").nth(1).unwrap().parse::<i64>().unwrap();
-        let b_y = b_parts[1].split("+").nth(1).unwrap().parse::<i64>().unwrap();
-
-        // Parse Prize
-        let prize_line = lines[i + 2];
-        let prize_parts: Vec<&str> = prize_line.split(", ").collect();
-        let prize_x = prize_parts[0].split("=").nth(1).unwrap().parse::<i64>().unwrap();
-        let prize_y = prize_parts[1].split("=").nth(1).unwrap().parse::<i64>().unwrap();
-
-        machines.push(Machine {
-            a_x,
-            a_y,
-            b_x,
-            b_y,
-            prize_x,
-            prize_y,
-        });
+    lines
+        .chunks(3)
+        .filter_map(|chunk| {
+            let [a_line, b_line, prize_line] = chunk else {
+                return None;
+            };
+            let (a_x, a_y) = parse_pair(a_line, '+')?;
+            let (b_x, b_y) = parse_pair(b_line, '+')?;
+            let (prize_x, prize_y) = parse_pair(prize_line, '=')?;
 
-        i += 3;
-    }
+            Some(Machine {
+                a_x,
+                a_y,
+                b_x,
+                b_y,
+                prize_x,
+                prize_y,
+            })
+        })
+        .collect()
+}
 
-    machines
+// "Button A: X+94, Y+34" with '+', "Prize: X=8400, Y=5400" with '='
+fn parse_pair(line: &str, sign: char) -> Option<(Signed, Signed)> {
+    let (x, y) = line.split_once(", ")?;
+    let x = x.split(sign).nth(1)?.parse().ok()?;
+    let y = y.split(sign).nth(1)?.parse().ok()?;
+    Some((x, y))
 }
 
-fn solve_part1(machines: &[Machine]) -> i64 {
+fn solve_part1(machines: &[Machine]) -> Signed {
     let mut total_tokens = 0;
 
     for machine in machines {
         if let Some(tokens) = find_min_tokens(machine, 100) {
-            total_tokens += tokens;
+            total_tokens = total_tokens.plus(tokens);
         }
     }
 
     total_tokens
 }
 
-fn solve_part2(machines: &[Machine]) -> i64 {
+fn solve_part2(machines: &[Machine]) -> Signed {
     let mut total_tokens = 0;
-    const OFFSET: i64 = 10_000_000_000_000;
+    const OFFSET: Signed = 10_000_000_000_000;
 
     for machine in machines {
         let adjusted_machine = Machine {
@@ -87,57 +80,212 @@ fn solve_part2(machines: &[Machine]) -> i64 {
             a_y: machine.a_y,
             b_x: machine.b_x,
             b_y: machine.b_y,
-            prize_x: machine.prize_x + OFFSET,
-            prize_y: machine.prize_y + OFFSET,
+            prize_x: machine.prize_x.plus(OFFSET),
+            prize_y: machine.prize_y.plus(OFFSET),
         };
 
-        if let Some(tokens) = find_min_tokens(&adjusted_machine, i64::MAX) {
-            total_tokens += tokens;
+        if let Some(tokens) = find_min_tokens(&adjusted_machine, Signed::MAX) {
+            total_tokens = total_tokens.plus(tokens);
         }
     }
 
     total_tokens
 }
 
-fn find_min_tokens(machine: &Machine, max_presses: i64) -> Option<i64> {
-    // We need to solve:
+fn find_min_tokens(machine: &Machine, max_presses: Signed) -> Option<Signed> {
+    // We need whole numbers of presses a and b with:
     // a * a_x + b * b_x = prize_x
     // a * a_y + b * b_y = prize_y
-    //
-    // Using Cramer's rule:
-    // determinant = a_x * b_y - a_y * b_x
-    // a = (prize_x * b_y - prize_y * b_x) / determinant
-    // b = (a_x * prize_y - a_y * prize_x) / determinant
+    let buttons = [
+        vec![wide(machine.a_x), wide(machine.b_x)],
+        vec![wide(machine.a_y), wide(machine.b_y)],
+    ];
+    let prize = [wide(machine.prize_x), wide(machine.prize_y)];
+    let presses = linalg::solve_integer(&buttons, &prize)?;
+    let max = wide(max_presses);
+    let [a, b] = presses.particular[..] else {
+        unreachable!("two buttons")
+    };
 
-    let det = machine.a_x * machine.b_y - machine.a_y * machine.b_x;
+    let (a, b) = match presses.free[..] {
+        // the buttons move the claw different ways, so there's only the one answer
+        [] => (a, b),
+        // parallel buttons, the answers are a line a + t*da, b + t*db and the cost changes
+        // at the same rate all along it, so the cheapest is at one end of the part in range
+        [ref direction] => {
+            let [da, db] = direction[..] else {
+                unreachable!("two buttons")
+            };
+            let (a_lo, a_hi) = press_range(a, da, max);
+            let (b_lo, b_hi) = press_range(b, db, max);
+            let (lo, hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+            if lo > hi {
+                return None;
+            }
+            let t = if 3 * da + db >= 0 { lo } else { hi };
+            (a + t * da, b + t * db)
+        }
+        // neither button moves the claw, solve_integer already turned down every prize
+        // but (0, 0) and that one is won without pressing anything
+        _ => (0, 0),
+    };
 
-    if det == 0 {
-        return None; // No unique solution
+    // Check if solutions are non-negative and within limits
+    if a < 0 || b < 0 || a > max || b > max {
+        return None;
     }
 
-    let a_num = machine.prize_x * machine.b_y - machine.prize_y * machine.b_x;
-    let b_num = machine.a_x * machine.prize_y - machine.a_y * machine.prize_x;
+    // Calculate tokens: 3 per A press, 1 per B press
+    // the presses are at most max_presses so they fit back in Signed, the tokens might not
+    // and go through common::num so --features checked reports it
+    let narrow = |presses: i128| Signed::try_from(presses).expect("presses are within max");
+    Some(narrow(a).times(3).plus(narrow(b)))
+}
+
+// the solver works in i128 whatever Signed is
+fn wide(value: impl Into<i128>) -> i128 {
+    value.into()
+}
 
-    // Check if solutions are integers
-    if a_num % det != 0 || b_num % det != 0 {
-        return None;
+// the t for which 0 <= presses + t*direction <= max
+fn press_range(presses: i128, direction: i128, max: i128) -> (i128, i128) {
+    let room = max.saturating_sub(presses);
+    match direction.cmp(&0) {
+        Ordering::Equal if (0..=max).contains(&presses) => (i128::MIN, i128::MAX),
+        Ordering::Equal => (1, 0),
+        Ordering::Greater => (-(presses.div_euclid(direction)), room.div_euclid(direction)),
+        Ordering::Less => {
+            // same range for -t
+            let (lo, hi) = press_range(presses, -direction, max);
+            (-hi, -lo)
+        }
     }
+}
 
-    let a = a_num / det;
-    let b = b_num / det;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
 
-    // Check if solutions are non-negative and within limits
-    if a < 0 || b < 0 || a > max_presses || b > max_presses {
-        return None;
+    // try every press count up to the limit and keep the cheapest hit
+    fn min_tokens_by_search(machine: &Machine, max_presses: Signed) -> Option<Signed> {
+        let mut best = None;
+        for a in 0..=max_presses {
+            for b in 0..=max_presses {
+                if a * machine.a_x + b * machine.b_x == machine.prize_x
+                    && a * machine.a_y + b * machine.b_y == machine.prize_y
+                {
+                    let tokens = a * 3 + b;
+                    best = Some(best.map_or(tokens, |t: Signed| t.min(tokens)));
+                }
+            }
+        }
+        best
     }
 
-    // Verify the solution
-    if a * machine.a_x + b * machine.b_x != machine.prize_x
-        || a * machine.a_y + b * machine.b_y != machine.prize_y
-    {
-        return None;
+    #[test]
+    fn solver_matches_search() {
+        let mut rng = Rng::new(0x2024_0013);
+        let mut range = |lo: i64, hi: i64| rng.range(lo, hi) as Signed;
+        let mut solvable = 0;
+        while solvable < 200 {
+            let mut machine = Machine {
+                a_x: range(1, 30),
+                a_y: range(1, 30),
+                b_x: range(1, 30),
+                b_y: range(1, 30),
+                prize_x: 0,
+                prize_y: 0,
+            };
+
+            // every so often make the buttons parallel, those have many answers or none
+            if range(0, 3) == 0 {
+                let (p, q) = (range(1, 4), range(1, 4));
+                let (x, y) = (range(1, 8), range(1, 8));
+                (machine.a_x, machine.a_y) = (p * x, p * y);
+                (machine.b_x, machine.b_y) = (q * x, q * y);
+            }
+
+            // half the time aim the prize at a reachable point, otherwise anywhere
+            if range(0, 1) == 0 {
+                let (a, b) = (range(0, 20), range(0, 20));
+                machine.prize_x = a * machine.a_x + b * machine.b_x;
+                machine.prize_y = a * machine.a_y + b * machine.b_y;
+                solvable += 1;
+            } else {
+                machine.prize_x = range(0, 600);
+                machine.prize_y = range(0, 600);
+            }
+
+            assert_eq!(
+                find_min_tokens(&machine, 20),
+                min_tokens_by_search(&machine, 20),
+                "{:?}",
+                machine
+            );
+        }
     }
 
-    // Calculate tokens: 3 per A press, 1 per B press
-    Some(a * 3 + b)
+    const EXAMPLE: &str = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+";
+
+    #[test]
+    fn skips_machines_that_dont_read() {
+        // crashes found by fuzzing, a missing line or a missing number unwrapped on None
+        assert_eq!(parse_input("Button A: X+94, Y+34\n").len(), 0);
+        let broken = EXAMPLE.replace("Y+67", "Y67");
+        assert_eq!(solve_part1(&parse_input(&broken)), 0);
+        assert_eq!(solve_part1(&parse_input(EXAMPLE)), 280);
+    }
+
+    #[test]
+    fn buttons_that_dont_move_the_claw() {
+        // two zero buttons used to panic instead of giving up or winning for free
+        let stuck = "Button A: X+0, Y+0\nButton B: X+0, Y+0\nPrize: X=5, Y=5\n";
+        assert_eq!(solve_part1(&parse_input(stuck)), 0);
+        let home = "Button A: X+0, Y+0\nButton B: X+0, Y+0\nPrize: X=0, Y=0\n";
+        let machines = parse_input(home);
+        assert_eq!(find_min_tokens(&machines[0], 100), Some(0));
+    }
+
+    #[test]
+    fn too_many_tokens() {
+        // 9000010000000000000 A presses fit in an i64 but three tokens for each dont,
+        // this used to panic converting the tokens back from i128 even with --features checked
+        let machines = parse_input(
+            "Button A: X+1, Y+1\nButton B: X+1, Y+2\n\
+             Prize: X=9000000000000000000, Y=9000000000000000000\n",
+        );
+        assert_eq!(num::checked(|| solve_part1(&machines)), Ok(0));
+        #[cfg(all(feature = "checked", not(feature = "wide")))]
+        assert_eq!(
+            num::checked(|| solve_part2(&machines)).unwrap_err().to_string(),
+            "arithmetic overflow: 9000010000000000000 * 3 does not fit in i64 (try --features wide)"
+        );
+        #[cfg(feature = "wide")]
+        assert_eq!(solve_part2(&machines), 27000030000000000000);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            let machines = parse_input(input);
+            solve_part1(&machines);
+            solve_part2(&machines);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let machines = parse_input(&common::input::load("src/input.txt"));
+        assert_eq!(solve_part1(&machines), 34787);
+        assert_eq!(solve_part2(&machines), 85644161121698);
+    }
 }
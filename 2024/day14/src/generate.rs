@@ -0,0 +1,45 @@
+use common::rng::Rng;
+
+use crate::{HEIGHT, WIDTH};
+
+// `size` robots scattered over the room with velocities in the same range as the real input
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut out = String::new();
+
+    for _ in 0..size {
+        let px = rng.range(0, WIDTH as i64 - 1);
+        let py = rng.range(0, HEIGHT as i64 - 1);
+        let vx = rng.range(-99, 99);
+        let vy = rng.range(-99, 99);
+        out.push_str(&format!("p={},{} v={},{}\n", px, py, vx, vy));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_robots, solve_part1, solve_part2};
+
+    #[test]
+    fn same_seed_same_robots() {
+        assert_eq!(generate(300, 14), generate(300, 14));
+        assert_ne!(generate(300, 14), generate(300, 15));
+    }
+
+    #[test]
+    fn generated_robots_parse_and_solve() {
+        let robots = parse_robots(&generate(300, 1));
+        assert_eq!(robots.len(), 300);
+        assert!(
+            robots
+                .iter()
+                .all(|r| (0..WIDTH).contains(&r.px) && (0..HEIGHT).contains(&r.py))
+        );
+        solve_part1(&robots);
+        // the frames repeat every WIDTH * HEIGHT seconds
+        assert!(solve_part2(&robots) < (WIDTH * HEIGHT) as usize);
+    }
+}
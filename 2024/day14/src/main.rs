@@ -1,4 +1,9 @@
-use std::fs;
+use std::io;
+
+use common::render::{Rgb, Scene};
+use common::sim::{Colour, Frame, Simulation};
+
+mod generate;
 
 #[derive(Debug, Clone, Copy)]
 struct Robot {
@@ -12,13 +17,71 @@ const WIDTH: i32 = 101;
 const HEIGHT: i32 = 103;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load_or_generate("src/input.txt", generate::generate);
     let robots = parse_robots(&input);
 
     let part1 = solve_part1(&robots);
     let part2 = solve_part2(&robots);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
+
+    // `--render <file> [--second <t>]`, defaults to the tree frame
+    if let Some(path) = common::render::requested() {
+        let second = std::env::args()
+            .skip_while(|arg| arg != "--second")
+            .nth(1)
+            .map_or(part2 as i32, |s| s.parse().expect("Second must be a number"));
+        if let Err(e) = render(&robots, second, &path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if common::sim::requested() {
+        common::sim::play(&mut Robots {
+            robots,
+            second: 0,
+            tree: part2 as i32,
+        });
+    }
+}
+
+// the robots one second at a time, stopping on the tree at the part 2 answer
+// so the last frame is the picture and a non-terminal run ends
+struct Robots {
+    robots: Vec<Robot>,
+    second: i32,
+    tree: i32,
+}
+
+impl Simulation for Robots {
+    fn step(&mut self) -> bool {
+        if self.second == self.tree {
+            return false;
+        }
+        self.second += 1;
+        true
+    }
+
+    fn render(&self) -> Frame {
+        let mut frame = Frame::new(WIDTH as usize, HEIGHT as usize, ' ');
+        for robot in &self.robots {
+            let (x, y) = position_after(robot, self.second);
+            frame.set(y as usize, x as usize, '#', Some(Colour::Green));
+        }
+        frame.set_status(format!("second {}", self.second));
+        frame
+    }
+}
+
+fn render(robots: &[Robot], second: i32, path: &str) -> io::Result<()> {
+    let mut scene = Scene::new(WIDTH as usize, HEIGHT as usize, Rgb(15, 25, 35));
+    let positions = robots.iter().map(|r| {
+        let (x, y) = position_after(r, second);
+        (y as usize, x as usize)
+    });
+    scene.mark(positions, Rgb(80, 220, 100));
+    scene.save(path, 6)
 }
 
 fn parse_robots(input: &str) -> Vec<Robot> {
@@ -30,30 +93,33 @@ fn parse_robots(input: &str) -> Vec<Robot> {
             let (px, py) = p.strip_prefix("p=")?.split_once(',')?;
             let (vx, vy) = v.split_once(',')?;
 
+            // everything wraps, so only the remainders matter and
+            // the jumps in position_after cant overflow
+            let x = |s: &str| s.parse().ok().map(|n: i32| n.rem_euclid(WIDTH));
+            let y = |s: &str| s.parse().ok().map(|n: i32| n.rem_euclid(HEIGHT));
             Some(Robot {
-                px: px.parse().ok()?,
-                py: py.parse().ok()?,
-                vx: vx.parse().ok()?,
-                vy: vy.parse().ok()?,
+                px: x(px)?,
+                py: y(py)?,
+                vx: x(vx)?,
+                vy: y(vy)?,
             })
         })
         .collect()
 }
 
+fn position_after(robot: &Robot, t: i32) -> (i32, i32) {
+    // rem_euclid handles negative velocities correctly (wrapping)
+    let x = (robot.px + robot.vx * t).rem_euclid(WIDTH);
+    let y = (robot.py + robot.vy * t).rem_euclid(HEIGHT);
+    (x, y)
+}
+
 fn solve_part1(robots: &[Robot]) -> usize {
     // we can compute final position directly without simulation
     // position after t seconds: (px + vx * t) mod WIDTH
     // This is O(n) instead of O(n * t) if we simulated each step
 
-    let positions: Vec<(i32, i32)> = robots
-        .iter()
-        .map(|r| {
-            // rem_euclid handles negative velocities correctly (wrapping)
-            let x = (r.px + r.vx * 100).rem_euclid(WIDTH);
-            let y = (r.py + r.vy * 100).rem_euclid(HEIGHT);
-            (x, y)
-        })
-        .collect();
+    let positions: Vec<(i32, i32)> = robots.iter().map(|r| position_after(r, 100)).collect();
 
     // count robots in each quadrant
     // robots exactly in the middle (on dividing lines) dont count
@@ -84,54 +150,148 @@ fn solve_part2(robots: &[Robot]) -> usize {
     // instead of pattern matching detect when robots form tight cluster
     // variance minimization is O(n) per iteration vs expensive image analysis
 
-    let mut min_variance = f64::MAX;
-    let mut best_time = 0;
+    // x and y move independently, so each axis repeats on its own period
+    // find the tightest x frame within one x period and the tightest y frame
+    // within one y period, then the tree is the time that matches both
+    let xs: Vec<(i32, i32)> = robots.iter().map(|r| (r.px, r.vx)).collect();
+    let ys: Vec<(i32, i32)> = robots.iter().map(|r| (r.py, r.vy)).collect();
+
+    let threads = common::par::threads();
+    let (best_x, period_x) = tightest_frame(threads, &xs, WIDTH);
+    let (best_y, period_y) = tightest_frame(threads, &ys, HEIGHT);
 
-    // the pattern repeats after WIDTH * HEIGHT seconds (LCM of dimensions)
-    // but it will appear much sooner (scan first 10k seconds)
-    for t in 0..10000 {
-        let positions: Vec<(i32, i32)> = robots
+    // chinese remainder by stepping: t = best_x (mod period_x), t = best_y (mod period_y)
+    (0..period_y)
+        .map(|k| best_x + k * period_x)
+        .find(|t| t % period_y == best_y)
+        .expect("axis periods should be coprime")
+}
+
+// returns the time within one period where this axis has the lowest variance, and the period
+fn tightest_frame(threads: usize, axis: &[(i32, i32)], size: i32) -> (usize, usize) {
+    let step = |positions: &Vec<i32>| {
+        Some(
+            positions
+                .iter()
+                .zip(axis)
+                .map(|(&p, &(_, v))| (p + v).rem_euclid(size))
+                .collect(),
+        )
+    };
+    let start: Vec<i32> = axis.iter().map(|&(p, _)| p).collect();
+
+    // every robot wraps back to where it started, so the cycle starts at 0
+    let cycle = common::cycle::brent(start, step).expect("positions always cycle");
+
+    // each frame is worked out straight from the start positions so the frames can be
+    // scored independently, on as many threads as there are
+    let times: Vec<i32> = (0..cycle.length as i32).collect();
+    let variances = common::par::map_with(threads, &times, |&t| {
+        let positions: Vec<i32> = axis
             .iter()
-            .map(|r| {
-                let x = (r.px + r.vx * t).rem_euclid(WIDTH);
-                let y = (r.py + r.vy * t).rem_euclid(HEIGHT);
-                (x, y)
-            })
+            .map(|&(p, v)| (p + v * t).rem_euclid(size))
             .collect();
+        calculate_variance(&positions)
+    });
 
-        // calculate variance as clustering metric
-        // low variance = tight cluster = potential tree
-        let variance = calculate_variance(&positions);
-
-        if variance < min_variance {
-            min_variance = variance;
-            best_time = t;
+    // keeps the earliest frame on ties
+    let mut best = (f64::MAX, 0);
+    for (t, &variance) in variances.iter().enumerate() {
+        if variance < best.0 {
+            best = (variance, t);
         }
     }
 
-    best_time as usize
+    (best.1, cycle.length)
 }
 
-fn calculate_variance(positions: &[(i32, i32)]) -> f64 {
+fn calculate_variance(values: &[i32]) -> f64 {
     // bit of math flex: variance = E[X²] - E[X]²
-    // computing both x and y variance simultaneously
 
-    let n = positions.len() as f64;
+    let n = values.len() as f64;
 
-    let (sum_x, sum_y, sum_x2, sum_y2) = positions
-        .iter()
-        .fold((0.0, 0.0, 0.0, 0.0), |(sx, sy, sx2, sy2), &(x, y)| {
-            let xf = x as f64;
-            let yf = y as f64;
-            (sx + xf, sy + yf, sx2 + xf * xf, sy2 + yf * yf)
-        });
+    let (sum, sum_sq) = values.iter().fold((0.0, 0.0), |(s, s2), &x| {
+        let xf = x as f64;
+        (s + xf, s2 + xf * xf)
+    });
 
-    let mean_x = sum_x / n;
-    let mean_y = sum_y / n;
+    let mean = sum / n;
+    (sum_sq / n) - (mean * mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    // move one second at a time, wrapping at the edges
+    fn position_by_stepping(robot: &Robot, t: i32) -> (i32, i32) {
+        let (mut x, mut y) = (robot.px, robot.py);
+        for _ in 0..t {
+            x = (x + robot.vx + WIDTH) % WIDTH;
+            y = (y + robot.vy + HEIGHT) % HEIGHT;
+        }
+        (x, y)
+    }
 
-    let var_x = (sum_x2 / n) - (mean_x * mean_x);
-    let var_y = (sum_y2 / n) - (mean_y * mean_y);
+    #[test]
+    fn real_input() {
+        let robots = parse_robots(&common::input::load("src/input.txt"));
+        assert_eq!(solve_part1(&robots), 230436441);
+        assert_eq!(solve_part2(&robots), 8270);
+    }
 
-    // total variance combines both dimensions
-    var_x + var_y
-}
\ No newline at end of file
+    #[test]
+    fn any_thread_count_picks_the_same_frame() {
+        let robots = parse_robots(&common::input::load("src/input.txt"));
+        let xs: Vec<(i32, i32)> = robots.iter().map(|r| (r.px, r.vx)).collect();
+        let ys: Vec<(i32, i32)> = robots.iter().map(|r| (r.py, r.vy)).collect();
+        for threads in [2, 3, 8] {
+            assert_eq!(tightest_frame(threads, &xs, WIDTH), tightest_frame(1, &xs, WIDTH));
+            assert_eq!(tightest_frame(threads, &ys, HEIGHT), tightest_frame(1, &ys, HEIGHT));
+        }
+    }
+
+    #[test]
+    fn big_numbers_wrap() {
+        // a velocity this big overflowed i32 long before 100 seconds
+        let robots = parse_robots("p=0,0 v=99999999,-99999999\n");
+        assert_eq!(
+            (robots[0].vx, robots[0].vy),
+            (99999999 % WIDTH, HEIGHT - 99999999 % HEIGHT)
+        );
+        assert_eq!(solve_part1(&robots), 0);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        let example = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\np=2,0 v=2,-1\n";
+        common::fuzz::check(&[example], 5_000, |input| {
+            let robots = parse_robots(input);
+            solve_part1(&robots);
+            solve_part2(&robots);
+        });
+    }
+
+    #[test]
+    fn jump_matches_stepping() {
+        let mut rng = Rng::new(0x2024_0014);
+        let mut range = |lo: i32, hi: i32| rng.range(lo.into(), hi.into()) as i32;
+        for _ in 0..2_000 {
+            let robot = Robot {
+                px: range(0, WIDTH - 1),
+                py: range(0, HEIGHT - 1),
+                vx: range(-WIDTH + 1, WIDTH - 1),
+                vy: range(-HEIGHT + 1, HEIGHT - 1),
+            };
+            let t = range(0, 300);
+            assert_eq!(
+                position_after(&robot, t),
+                position_by_stepping(&robot, t),
+                "{:?} t={}",
+                robot,
+                t
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::fs;
+use common::sim::{Colour, Frame, Simulation};
 
 type Pos = (usize, usize);
 
@@ -31,16 +31,29 @@ struct WideWarehouse {
 }
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let (warehouse, moves) = parse_input(&input);
+    let input = common::input::load("src/input.txt");
+    let (warehouse, moves) = parse_input(&input).expect(BAD_MAP);
 
     let part1 = solve_part1(warehouse.clone(), &moves);
     let part2 = solve_part2(warehouse, &moves);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
+
+    if common::sim::requested() {
+        let (warehouse, moves) = parse_input(&input).expect(BAD_MAP);
+        common::sim::play(&mut WideRun {
+            warehouse: scale_warehouse(&warehouse),
+            moves,
+            next: 0,
+        });
+    }
 }
 
-fn parse_input(input: &str) -> (Warehouse, Vec<char>) {
+const BAD_MAP: &str = "The map must be a rectangle of #.O@ walled in all round with one robot";
+
+// None for a map the robot could walk off or that isnt one of these,
+// moves it doesnt know are skipped
+fn parse_input(input: &str) -> Option<(Warehouse, Vec<char>)> {
     // handle both unix and windows line endings
     let normalized = input.replace("\r\n", "\n");
     let parts: Vec<&str> = normalized.split("\n\n").collect();
@@ -48,7 +61,7 @@ fn parse_input(input: &str) -> (Warehouse, Vec<char>) {
     let moves_str = if parts.len() > 1 { parts[1] } else { "" };
 
     let mut grid = Vec::new();
-    let mut robot = (0, 0);
+    let mut robots = Vec::new();
 
     for (r, line) in map_str.lines().enumerate() {
         let mut row = Vec::new();
@@ -58,19 +71,36 @@ fn parse_input(input: &str) -> (Warehouse, Vec<char>) {
                 '.' => Cell::Empty,
                 'O' => Cell::Box,
                 '@' => {
-                    robot = (r, c);
+                    robots.push((r, c));
                     Cell::Robot
                 }
-                _ => panic!("Unknown cell: {}", ch),
+                _ => return None,
             };
             row.push(cell);
         }
         grid.push(row);
     }
 
+    // every move looks one cell past the robot or the boxes without checking,
+    // which is only safe inside a wall
+    let width = grid.first()?.len();
+    let walled = grid.iter().enumerate().all(|(r, row)| {
+        row.len() == width
+            && row.iter().enumerate().all(|(c, &cell)| {
+                let edge = r == 0 || r == grid.len() - 1 || c == 0 || c == width - 1;
+                !edge || cell == Cell::Wall
+            })
+    });
+    let [robot] = robots[..] else {
+        return None;
+    };
+    if !walled {
+        return None;
+    }
+
     let moves = moves_str.chars().filter(|&c| c != '\n').collect();
 
-    (Warehouse { grid, robot }, moves)
+    Some((Warehouse { grid, robot }, moves))
 }
 
 fn solve_part1(mut warehouse: Warehouse, moves: &[char]) -> usize {
@@ -96,7 +126,7 @@ fn try_move(warehouse: &mut Warehouse, dir: char) {
 
     // check whats ahead without allocating
     match warehouse.grid[nr][nc] {
-        Cell::Wall => return, // cant move into wall
+        Cell::Wall => {} // cant move into wall
         Cell::Empty => {
             // simple case just move robot
             warehouse.grid[r][c] = Cell::Empty;
@@ -160,6 +190,50 @@ fn solve_part2(warehouse: Warehouse, moves: &[char]) -> usize {
     calculate_gps_sum_wide(&wide)
 }
 
+// the part 2 robot working through its moves, one per step
+struct WideRun {
+    warehouse: WideWarehouse,
+    moves: Vec<char>,
+    next: usize,
+}
+
+impl Simulation for WideRun {
+    fn step(&mut self) -> bool {
+        let Some(&dir) = self.moves.get(self.next) else {
+            return false;
+        };
+        try_move_wide(&mut self.warehouse, dir);
+        self.next += 1;
+        true
+    }
+
+    fn render(&self) -> Frame {
+        let grid = &self.warehouse.grid;
+        let mut frame = Frame::new(grid[0].len(), grid.len(), '.');
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                match cell {
+                    WideCell::Empty => {}
+                    WideCell::Wall => frame.set(r, c, '#', None),
+                    WideCell::BoxLeft => frame.set(r, c, '[', Some(Colour::Yellow)),
+                    WideCell::BoxRight => frame.set(r, c, ']', Some(Colour::Yellow)),
+                    WideCell::Robot => frame.set(r, c, '@', Some(Colour::Red)),
+                }
+            }
+        }
+
+        let last = self.next.checked_sub(1).map_or(' ', |i| self.moves[i]);
+        frame.set_status(format!(
+            "move {}/{} {}  gps {}",
+            self.next,
+            self.moves.len(),
+            last,
+            calculate_gps_sum_wide(&self.warehouse)
+        ));
+        frame
+    }
+}
+
 fn scale_warehouse(warehouse: &Warehouse) -> WideWarehouse {
     // directly build wide grid without intermediate allocations
     let mut wide_grid = Vec::new();
@@ -213,7 +287,7 @@ fn try_move_wide_horizontal(warehouse: &mut WideWarehouse, _dir: char, dr: isize
     let nc = (c as isize + dc) as usize;
 
     match warehouse.grid[nr][nc] {
-        WideCell::Wall => return,
+        WideCell::Wall => {}
         WideCell::Empty => {
             warehouse.grid[r][c] = WideCell::Empty;
             warehouse.grid[nr][nc] = WideCell::Robot;
@@ -260,7 +334,7 @@ fn try_move_wide_vertical(warehouse: &mut WideWarehouse, _dir: char, dr: isize)
     let nr = (r as isize + dr) as usize;
 
     match warehouse.grid[nr][c] {
-        WideCell::Wall => return,
+        WideCell::Wall => {}
         WideCell::Empty => {
             warehouse.grid[r][c] = WideCell::Empty;
             warehouse.grid[nr][c] = WideCell::Robot;
@@ -353,3 +427,57 @@ fn calculate_gps_sum_wide(warehouse: &WideWarehouse) -> usize {
         })
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<
+";
+
+    fn solve(input: &str) -> (usize, usize) {
+        let (warehouse, moves) = parse_input(input).expect(BAD_MAP);
+        (solve_part1(warehouse.clone(), &moves), solve_part2(warehouse, &moves))
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(SMALL).0, 2028);
+    }
+
+    #[test]
+    fn turns_down_maps_the_robot_could_leave() {
+        // crashes found by fuzzing, walking off an open edge indexed past the grid
+        assert!(parse_input("@.\n\n<<").is_none());
+        assert!(parse_input("###\n#@.\n###\n\n>>").is_none());
+        assert!(parse_input("###\n#@#\n#@#\n###\n").is_none());
+        assert!(parse_input("###\n#x#\n###\n").is_none());
+        assert!(parse_input("").is_none());
+        assert_eq!(solve("####\n#@O#\n####\n\n>x>\n"), (102, 104));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[SMALL], 20_000, |input| {
+            if let Some((warehouse, moves)) = parse_input(input) {
+                solve_part1(warehouse.clone(), &moves);
+                solve_part2(warehouse, &moves);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        assert_eq!(solve(&common::input::load("src/input.txt")), (1514353, 1533076));
+    }
+}
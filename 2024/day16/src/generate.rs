@@ -0,0 +1,92 @@
+use common::rng::Rng;
+
+// square maze `size` tiles across with S in the bottom left and E in the top right
+// carved as a perfect maze first, then some walls are knocked out so there are
+// loops and more than one best path, like the real input
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+
+    // walls sit on even coordinates so the side has to be odd
+    let side = (size.max(5) - 1) / 2 * 2 + 1;
+    let cells = side / 2;
+    let mut grid = vec![vec!['#'; side]; side];
+
+    // iterative depth first carve over the odd coordinates
+    let mut visited = vec![vec![false; cells]; cells];
+    let mut stack = vec![(cells - 1, 0)];
+    visited[cells - 1][0] = true;
+    grid[side - 2][1] = '.';
+
+    while let Some(&(r, c)) = stack.last() {
+        let mut options = Vec::new();
+        if r > 0 && !visited[r - 1][c] {
+            options.push((r - 1, c));
+        }
+        if r + 1 < cells && !visited[r + 1][c] {
+            options.push((r + 1, c));
+        }
+        if c > 0 && !visited[r][c - 1] {
+            options.push((r, c - 1));
+        }
+        if c + 1 < cells && !visited[r][c + 1] {
+            options.push((r, c + 1));
+        }
+
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let (nr, nc) = options[rng.below(options.len() as u64) as usize];
+        visited[nr][nc] = true;
+        grid[2 * nr + 1][2 * nc + 1] = '.';
+        // open the wall between the two cells
+        grid[r + nr + 1][c + nc + 1] = '.';
+        stack.push((nr, nc));
+    }
+
+    // knock out roughly one in ten interior walls that sit between two open tiles
+    for r in 1..side - 1 {
+        for c in 1..side - 1 {
+            let horizontal = grid[r][c - 1] == '.' && grid[r][c + 1] == '.';
+            let vertical = grid[r - 1][c] == '.' && grid[r + 1][c] == '.';
+            if grid[r][c] == '#' && (horizontal ^ vertical) && rng.chance(10) {
+                grid[r][c] = '.';
+            }
+        }
+    }
+
+    grid[side - 2][1] = 'S';
+    grid[1][side - 2] = 'E';
+
+    let mut out = String::with_capacity(side * (side + 1));
+    for row in grid {
+        out.extend(row);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BAD_MAZE, parse_maze, solve_part1, solve_part2};
+
+    #[test]
+    fn same_seed_same_maze() {
+        assert_eq!(generate(41, 16), generate(41, 16));
+        assert_ne!(generate(41, 16), generate(41, 17));
+    }
+
+    #[test]
+    fn generated_maze_parses_and_solves() {
+        let input = generate(41, 1);
+        assert_eq!(input.lines().count(), 41);
+        // S and E are opposite corners, so at least the manhattan distance
+        // and one turn to get from heading east to heading north
+        let steps = 2 * (41 - 3);
+        let maze = parse_maze(&input).expect(BAD_MAZE);
+        assert!(solve_part1(&maze) >= steps + 1000);
+        assert!(solve_part2(&maze) > steps);
+    }
+}
@@ -1,7 +1,13 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::fs;
+use std::collections::{BinaryHeap, HashSet};
+use std::io;
+
+use common::cells::{CellMap, DirMap};
+use common::render::{Palette, Rgb, Scene};
+
+mod generate;
 
 type Pos = (usize, usize);
+type Maze = (Vec<Vec<char>>, Pos, Pos);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Dir {
@@ -12,6 +18,13 @@ enum Dir {
 }
 
 impl Dir {
+    const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    // slot in a DirMap
+    fn index(self) -> usize {
+        self as usize
+    }
+
     fn turn_left(self) -> Self {
         match self {
             Dir::North => Dir::West,
@@ -62,46 +75,77 @@ impl PartialOrd for State {
 }
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
+    let input = common::input::load_or_generate("src/input.txt", generate::generate);
+    let maze = parse_maze(&input).expect(BAD_MAZE);
+    let part1 = solve_part1(&maze);
+    let part2 = solve_part2(&maze);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
+
+    if let Some(path) = common::render::requested()
+        && let Err(e) = render(&input, &maze, &path)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+const BAD_MAZE: &str = "The maze must be a rectangle with one S and one E";
+
+fn solve_part1((grid, start, end): &Maze) -> usize {
+    dijkstra(grid, *start, *end)
 }
 
-fn solve_part1(input: &str) -> usize {
-    let (grid, start, end) = parse_maze(input);
-    dijkstra(&grid, start, end)
+fn solve_part2((grid, start, end): &Maze) -> usize {
+    best_path_tiles(grid, *start, *end).0.len()
 }
 
-fn solve_part2(input: &str) -> usize {
-    let (grid, start, end) = parse_maze(input);
-    count_best_path_tiles(&grid, start, end)
+// maze with every best path tile marked and one of the best routes drawn through it
+fn render(input: &str, (grid, start, end): &Maze, path: &str) -> io::Result<()> {
+    let (tiles, route) = best_path_tiles(grid, *start, *end);
+
+    let palette = Palette::new()
+        .with('#', Rgb(40, 40, 50))
+        .with('.', Rgb(225, 225, 215))
+        .with('S', Rgb(60, 170, 80))
+        .with('E', Rgb(200, 60, 60));
+    let mut scene = Scene::from_chars(input, &palette);
+    scene.mark(tiles, Rgb(240, 190, 40));
+    scene.path(route, Rgb(30, 90, 200));
+    scene.save(path, 6)
 }
 
-fn parse_maze(input: &str) -> (Vec<Vec<char>>, Pos, Pos) {
+// None for a maze the searches cant index, they size everything off the first row
+fn parse_maze(input: &str) -> Option<Maze> {
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    let width = grid.first()?.len();
+    if grid.iter().any(|row| row.len() != width) {
+        return None;
+    }
 
-    let mut start = (0, 0);
-    let mut end = (0, 0);
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
 
     for (r, row) in grid.iter().enumerate() {
         for (c, &cell) in row.iter().enumerate() {
             if cell == 'S' {
-                start = (r, c);
+                starts.push((r, c));
             } else if cell == 'E' {
-                end = (r, c);
+                ends.push((r, c));
             }
         }
     }
 
-    (grid, start, end)
+    let (&[start], &[end]) = (&starts[..], &ends[..]) else {
+        return None;
+    };
+    Some((grid, start, end))
 }
 
 fn dijkstra(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
     // state includes direction to avoid revisiting with different costs
     let mut heap = BinaryHeap::new();
-    let mut dist: HashMap<(Pos, Dir), usize> = HashMap::new();
+    let mut dist: DirMap<usize> = CellMap::new(grid.len(), grid[0].len(), [usize::MAX; 4]);
 
     // start facing east
     heap.push(State {
@@ -109,7 +153,7 @@ fn dijkstra(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
         pos: start,
         dir: Dir::East,
     });
-    dist.insert((start, Dir::East), 0);
+    dist[start][Dir::East.index()] = 0;
 
     while let Some(State { cost, pos, dir }) = heap.pop() {
         // reached end
@@ -118,41 +162,38 @@ fn dijkstra(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
         }
 
         // skip if weve found a better path
-        if let Some(&best) = dist.get(&(pos, dir)) {
-            if cost > best {
-                continue;
-            }
+        if cost > dist[pos][dir.index()] {
+            continue;
         }
 
         // three possible moves: forward, turn left, turn right
 
         // move forward (cost +1)
-        if let Some(next_pos) = dir.forward(pos) {
-            if next_pos.0 < grid.len()
-                && next_pos.1 < grid[0].len()
-                && grid[next_pos.0][next_pos.1] != '#'
-            {
-                let next_cost = cost + 1;
-                let key = (next_pos, dir);
-
-                if next_cost < *dist.get(&key).unwrap_or(&usize::MAX) {
-                    dist.insert(key, next_cost);
-                    heap.push(State {
-                        cost: next_cost,
-                        pos: next_pos,
-                        dir,
-                    });
-                }
+        if let Some(next_pos) = dir.forward(pos)
+            && next_pos.0 < grid.len()
+            && next_pos.1 < grid[0].len()
+            && grid[next_pos.0][next_pos.1] != '#'
+        {
+            let next_cost = cost + 1;
+            let best = &mut dist[next_pos][dir.index()];
+
+            if next_cost < *best {
+                *best = next_cost;
+                heap.push(State {
+                    cost: next_cost,
+                    pos: next_pos,
+                    dir,
+                });
             }
         }
 
         // turn left (cost +1000)
         let left_dir = dir.turn_left();
         let left_cost = cost + 1000;
-        let left_key = (pos, left_dir);
+        let left_best = &mut dist[pos][left_dir.index()];
 
-        if left_cost < *dist.get(&left_key).unwrap_or(&usize::MAX) {
-            dist.insert(left_key, left_cost);
+        if left_cost < *left_best {
+            *left_best = left_cost;
             heap.push(State {
                 cost: left_cost,
                 pos,
@@ -163,10 +204,10 @@ fn dijkstra(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
         // turn right (cost +1000)
         let right_dir = dir.turn_right();
         let right_cost = cost + 1000;
-        let right_key = (pos, right_dir);
+        let right_best = &mut dist[pos][right_dir.index()];
 
-        if right_cost < *dist.get(&right_key).unwrap_or(&usize::MAX) {
-            dist.insert(right_key, right_cost);
+        if right_cost < *right_best {
+            *right_best = right_cost;
             heap.push(State {
                 cost: right_cost,
                 pos,
@@ -178,18 +219,20 @@ fn dijkstra(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
     usize::MAX // no path found
 }
 
-fn count_best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
+// every tile on some best path, plus one best route from start to end
+fn best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> (HashSet<Pos>, Vec<Pos>) {
     // modified dijkstra that tracks predecessors for all optimal paths
+    let (rows, cols) = (grid.len(), grid[0].len());
     let mut heap = BinaryHeap::new();
-    let mut dist: HashMap<(Pos, Dir), usize> = HashMap::new();
-    let mut predecessors: HashMap<(Pos, Dir), Vec<(Pos, Dir)>> = HashMap::new();
+    let mut dist: DirMap<usize> = CellMap::new(rows, cols, [usize::MAX; 4]);
+    let mut predecessors: DirMap<Vec<(Pos, Dir)>> = CellMap::new(rows, cols, Default::default());
 
     heap.push(State {
         cost: 0,
         pos: start,
         dir: Dir::East,
     });
-    dist.insert((start, Dir::East), 0);
+    dist[start][Dir::East.index()] = 0;
 
     let mut min_cost_to_end = usize::MAX;
 
@@ -206,10 +249,8 @@ fn count_best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
         }
 
         // skip if found better path
-        if let Some(&best) = dist.get(&(pos, dir)) {
-            if cost > best {
-                continue;
-            }
+        if cost > dist[pos][dir.index()] {
+            continue;
         }
 
         // explore neighbors
@@ -232,13 +273,14 @@ fn count_best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
                 }
 
                 let next_cost = cost + move_cost;
-                let key = (next_pos, next_dir);
-                let current_best = *dist.get(&key).unwrap_or(&usize::MAX);
+                let current_best = dist[next_pos][next_dir.index()];
+                let preds = &mut predecessors[next_pos][next_dir.index()];
 
                 if next_cost < current_best {
                     // found better path
-                    dist.insert(key, next_cost);
-                    predecessors.insert(key, vec![(pos, dir)]);
+                    dist[next_pos][next_dir.index()] = next_cost;
+                    preds.clear();
+                    preds.push((pos, dir));
                     heap.push(State {
                         cost: next_cost,
                         pos: next_pos,
@@ -246,7 +288,7 @@ fn count_best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
                     });
                 } else if next_cost == current_best {
                     // found equal path (add predecessor)
-                    predecessors.entry(key).or_default().push((pos, dir));
+                    preds.push((pos, dir));
                 }
             }
         }
@@ -257,29 +299,94 @@ fn count_best_path_tiles(grid: &[Vec<char>], start: Pos, end: Pos) -> usize {
     let mut stack = Vec::new();
 
     // find all end directions with optimal cost
-    for dir in [Dir::North, Dir::East, Dir::South, Dir::West] {
-        if let Some(&cost) = dist.get(&(end, dir)) {
-            if cost == min_cost_to_end {
-                stack.push((end, dir));
-            }
+    for dir in Dir::ALL {
+        if dist[end][dir.index()] == min_cost_to_end {
+            stack.push((end, dir));
         }
     }
 
-    let mut visited_states = HashSet::new();
+    // follow the first predecessor back from one end state for a single route
+    let mut route = Vec::new();
+    let mut current = stack.first().copied();
+    while let Some(state) = current {
+        if route.last() != Some(&state.0) {
+            route.push(state.0);
+        }
+        current = predecessors[state.0][state.1.index()].first().copied();
+    }
+    route.reverse();
 
-    while let Some(state) = stack.pop() {
-        if !visited_states.insert(state) {
+    let mut visited_states: DirMap<bool> = CellMap::new(rows, cols, [false; 4]);
+
+    while let Some((pos, dir)) = stack.pop() {
+        let seen = &mut visited_states[pos][dir.index()];
+        if *seen {
             continue;
         }
+        *seen = true;
+
+        visited_tiles.insert(pos);
+        stack.extend(&predecessors[pos][dir.index()]);
+    }
 
-        visited_tiles.insert(state.0);
+    (visited_tiles, route)
+}
 
-        if let Some(preds) = predecessors.get(&state) {
-            for &pred in preds {
-                stack.push(pred);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
+
+    fn solve(input: &str) -> (usize, usize) {
+        let maze = parse_maze(input).expect(BAD_MAZE);
+        (solve_part1(&maze), solve_part2(&maze))
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), (7036, 45));
+    }
+
+    #[test]
+    fn turns_down_mazes_the_search_cant_index() {
+        // crashes found by fuzzing, the maps were sized off grid[0]
+        assert!(parse_maze("").is_none());
+        assert!(parse_maze("S.E\n.").is_none());
+        assert!(parse_maze("S.\n.E\n.S").is_none());
+        assert!(parse_maze("S..").is_none());
+        assert_eq!(solve("S.E\n...\n"), (2, 3));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 2_000, |input| {
+            if let Some(maze) = parse_maze(input) {
+                solve_part1(&maze);
+                solve_part2(&maze);
             }
-        }
+        });
     }
 
-    visited_tiles.len()
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve(&input), (130536, 1024));
+    }
 }
@@ -1,4 +1,3 @@
-use std::fs;
 
 #[derive(Debug, Clone)]
 struct Computer {
@@ -99,29 +98,33 @@ impl Computer {
 }
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
+    let input = common::input::load("src/input.txt");
+    let debugger = parse_input(&input).expect(BAD_INPUT);
+    let part1 = solve_part1(&debugger);
+    let part2 = solve_part2(&debugger);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
 
-fn solve_part1(input: &str) -> String {
-    let (a, b, c, program) = parse_input(input);
-    let mut computer = Computer::new(a, b, c, program);
+const BAD_INPUT: &str = "Expected three registers, a blank line and a program of 3-bit numbers";
+
+type Debugger = (i64, i64, i64, Vec<u8>);
+
+fn solve_part1((a, b, c, program): &Debugger) -> String {
+    let mut computer = Computer::new(*a, *b, *c, program.clone());
     computer.run();
     computer.output_string()
 }
 
-fn solve_part2(input: &str) -> i64 {
-    let (_, b, c, program) = parse_input(input);
+fn solve_part2((_, b, c, program): &Debugger) -> i64 {
+    let (b, c) = (*b, *c);
 
     // work backwards from the output
     // the program processes A in 3-bit chunks, outputting one value per iteration
     // and dividing A by 8 (right shift 3) each time
     // we can build A from the least significant chunks working backwards
 
-    find_quine(0, 0, &program, b, c)
+    find_quine(0, 0, program, b, c)
 }
 
 fn find_quine(a: i64, depth: usize, program: &[u8], b: i64, c: i64) -> i64 {
@@ -162,31 +165,74 @@ fn find_quine(a: i64, depth: usize, program: &[u8], b: i64, c: i64) -> i64 {
     -1
 }
 
-fn parse_input(input: &str) -> (i64, i64, i64, Vec<u8>) {
+// None when a register or the program doesnt read, or an instruction is missing its operand
+fn parse_input(input: &str) -> Option<Debugger> {
     let lines: Vec<&str> = input.lines().collect();
-
-    let a = lines[0]
-        .strip_prefix("Register A: ")
-        .unwrap()
-        .parse()
-        .unwrap();
-    let b = lines[1]
-        .strip_prefix("Register B: ")
-        .unwrap()
-        .parse()
-        .unwrap();
-    let c = lines[2]
-        .strip_prefix("Register C: ")
-        .unwrap()
-        .parse()
-        .unwrap();
-
-    let program = lines[4]
-        .strip_prefix("Program: ")
-        .unwrap()
+    let register = |i: usize, name: &str| -> Option<i64> {
+        let line = lines.get(i)?.strip_prefix("Register ")?;
+        line.strip_prefix(name)?.strip_prefix(": ")?.parse().ok()
+    };
+
+    let a = register(0, "A")?;
+    let b = register(1, "B")?;
+    let c = register(2, "C")?;
+
+    let program: Vec<u8> = lines
+        .get(4)?
+        .strip_prefix("Program: ")?
         .split(',')
-        .map(|s| s.parse().unwrap())
-        .collect();
+        .map(|s| s.parse().ok().filter(|&n| n < 8))
+        .collect::<Option<_>>()?;
+    if !program.len().is_multiple_of(2) {
+        return None;
+    }
+
+    Some((a, b, c, program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(input: &str) -> (String, i64) {
+        let debugger = parse_input(input).expect(BAD_INPUT);
+        (solve_part1(&debugger), solve_part2(&debugger))
+    }
+
+    #[test]
+    fn example() {
+        let input = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        assert_eq!(solve(input).0, "4,6,3,5,6,3,5,2,1,0");
+        let input = "Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0\n";
+        assert_eq!(solve(input).1, 117440);
+    }
+
+    #[test]
+    fn turns_down_programs_that_dont_read() {
+        // crashes found by fuzzing, every field was unwrapped and an odd
+        // length program read its last operand past the end
+        let registers = "Register A: 1\nRegister B: 0\nRegister C: 0\n\n";
+        assert!(parse_input("").is_none());
+        assert!(parse_input(&format!("{registers}Program: 5,4,3")).is_none());
+        assert!(parse_input(&format!("{registers}Program: 5,8")).is_none());
+        let registers = registers.replace("B: 0", "B: x");
+        assert!(parse_input(&format!("{registers}Program: 5,4")).is_none());
+    }
+
+    #[test]
+    fn fuzz_parse() {
+        // only the parser, a random program neednt ever halt
+        let example = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        common::fuzz::check(&[example], 20_000, |input| {
+            parse_input(input);
+        });
+    }
 
-    (a, b, c, program)
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let (part1, part2) = solve(&input);
+        assert_eq!(part1, "6,7,5,2,1,3,5,1,7");
+        assert_eq!(part2, 216549846240877);
+    }
 }
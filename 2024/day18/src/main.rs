@@ -1,5 +1,4 @@
 use std::collections::VecDeque;
-use std::fs;
 
 const GRID_SIZE: usize = 71;
 const INITIAL_BYTES: usize = 1024;
@@ -7,9 +6,9 @@ const INITIAL_BYTES: usize = 1024;
 type Pos = (usize, usize);
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
+    let part2 = solve_part2(&input).expect("No byte ever cuts off the exit");
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
@@ -26,7 +25,8 @@ fn solve_part1(input: &str) -> usize {
     bfs(&grid, (0, 0), (GRID_SIZE - 1, GRID_SIZE - 1)).unwrap_or(0)
 }
 
-fn solve_part2(input: &str) -> String {
+// None when the exit stays reachable after every byte
+fn solve_part2(input: &str) -> Option<String> {
     let coords = parse_coords(input);
 
     // binary search for first blocking byte
@@ -51,8 +51,8 @@ fn solve_part2(input: &str) -> String {
         }
     }
 
-    let (x, y) = coords[left];
-    format!("{},{}", x, y)
+    let (x, y) = coords.get(left)?;
+    Some(format!("{},{}", x, y))
 }
 
 fn bfs(grid: &[[bool; GRID_SIZE]; GRID_SIZE], start: Pos, end: Pos) -> Option<usize> {
@@ -93,7 +93,38 @@ fn parse_coords(input: &str) -> Vec<Pos> {
             let mut parts = line.split(',');
             let x = parts.next()?.parse().ok()?;
             let y = parts.next()?.parse().ok()?;
-            Some((x, y))
+            // bytes that land off the grid cant get in the way
+            (x < GRID_SIZE && y < GRID_SIZE).then_some((x, y))
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_off_the_grid_and_an_open_exit() {
+        // crashes found by fuzzing, a byte past the edge indexed off the grid
+        // and a list that never blocks the exit searched past its end
+        assert_eq!(solve_part1("71,0\n0,71\n"), 140);
+        assert_eq!(solve_part2("71,0\n5,5\n"), None);
+        assert_eq!(solve_part1("1,0\n70,70\n0,1\n"), 0);
+        assert_eq!(solve_part2("1,0\n70,70\n0,1\n").as_deref(), Some("70,70"));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&["5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n"], 1_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 380);
+        assert_eq!(solve_part2(&input).as_deref(), Some("26,50"));
+    }
+}
@@ -1,68 +1,59 @@
-use std::collections::HashMap;
-use std::fs;
+use common::matching::AhoCorasick;
+use common::num::{self, Arith, Count};
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: common::alloc::Counting = common::alloc::Counting;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
+    let input = common::input::load("src/input.txt");
+    common::alloc::report("Part 1", || solve_part1(&input));
+    common::alloc::report("Part 2", || num::checked_or_exit(|| solve_part2(&input)));
 }
 
 fn solve_part1(input: &str) -> usize {
     let (patterns, designs) = parse_input(input);
-    let mut cache = HashMap::new();
+    let towels = towels(&patterns);
 
     designs
         .iter()
-        .filter(|design| can_make(design, &patterns, &mut cache))
+        .filter(|design| can_make(design, &towels))
         .count()
 }
 
-fn solve_part2(input: &str) -> usize {
+fn solve_part2(input: &str) -> Count {
     let (patterns, designs) = parse_input(input);
-    let mut cache = HashMap::new();
+    let towels = towels(&patterns);
 
-    designs
-        .iter()
-        .map(|design| count_ways(design, &patterns, &mut cache))
-        .sum()
+    num::sum(designs.iter().map(|design| count_ways(design, &towels)))
 }
 
-fn can_make(design: &str, patterns: &[String], cache: &mut HashMap<String, bool>) -> bool {
-    if design.is_empty() {
-        return true;
-    }
-
-    if let Some(&result) = cache.get(design) {
-        return result;
-    }
-
-    let result = patterns.iter().any(|pattern| {
-        design.starts_with(pattern) && can_make(&design[pattern.len()..], patterns, cache)
-    });
-
-    cache.insert(design.to_string(), result);
-    result
+fn towels(patterns: &[String]) -> AhoCorasick<u8> {
+    AhoCorasick::new(patterns.iter().map(|p| p.bytes()))
 }
 
-fn count_ways(design: &str, patterns: &[String], cache: &mut HashMap<String, usize>) -> usize {
-    if design.is_empty() {
-        return 1;
+// made[i]: the first i stripes of the design can be made from towels
+// every towel match in the design extends a made prefix ending where the match starts,
+// and matches come ordered by where they end so that prefix is already done
+fn can_make(design: &str, towels: &AhoCorasick<u8>) -> bool {
+    let mut made = vec![false; design.len() + 1];
+    made[0] = true;
+    for m in towels.find_iter(design.bytes()) {
+        if made[m.start] {
+            made[m.end] = true;
+        }
     }
+    made[design.len()]
+}
 
-    if let Some(&result) = cache.get(design) {
-        return result;
+// same as can_make but counting the ways to make each prefix
+fn count_ways(design: &str, towels: &AhoCorasick<u8>) -> Count {
+    let mut ways = vec![Count::ZERO; design.len() + 1];
+    ways[0] = Count::ONE;
+    for m in towels.find_iter(design.bytes()) {
+        ways[m.end] = ways[m.end].plus(ways[m.start]);
     }
-
-    let result = patterns
-        .iter()
-        .filter(|pattern| design.starts_with(pattern.as_str()))
-        .map(|pattern| count_ways(&design[pattern.len()..], patterns, cache))
-        .sum();
-
-    cache.insert(design.to_string(), result);
-    result
+    ways[design.len()]
 }
 
 fn parse_input(input: &str) -> (Vec<String>, Vec<String>) {
@@ -70,8 +61,10 @@ fn parse_input(input: &str) -> (Vec<String>, Vec<String>) {
 
     let patterns = lines
         .next()
-        .unwrap()
+        .unwrap_or("")
         .split(", ")
+        // an empty towel would match everywhere, and theres no such thing
+        .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
         .collect();
 
@@ -81,3 +74,49 @@ fn parse_input(input: &str) -> (Vec<String>, Vec<String>) {
 
     (patterns, designs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb
+";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 6);
+        assert_eq!(solve_part2(EXAMPLE), 16);
+    }
+
+    #[test]
+    fn no_empty_towels() {
+        // crashes found by fuzzing, an empty line or a stray ", " made an empty
+        // towel and the matcher refuses those
+        assert_eq!(solve_part1(""), 0);
+        assert_eq!(solve_part2("r, , b\n\nrb\nx\n"), Count::ONE);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 247);
+        assert_eq!(solve_part2(&input), 692596560138745);
+    }
+}
@@ -1,5 +1,3 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
 
 fn is_safe_report(levels: &[i32]) -> bool {
     if levels.len() < 2 {
@@ -9,7 +7,8 @@ fn is_safe_report(levels: &[i32]) -> bool {
     let mut is_increasing = None;
 
     for window in levels.windows(2) {
-        let diff = window[1] - window[0];
+        // in i64, the gap between two far apart i32s doesnt fit in one
+        let diff = window[1] as i64 - window[0] as i64;
         if diff.abs() < 1 || diff.abs() > 3 {
             return false;
         }
@@ -51,25 +50,72 @@ fn can_be_safe_with_removal(levels: &[i32]) -> bool {
     false
 }
 
-fn main() -> io::Result<()> {
-    let file_path = "src/input.txt";
+fn main() {
+    let input = common::input::load("src/input.txt");
+    let reports = parse_reports(&input).expect(BAD_INPUT);
+    println!("Part 1: {}", solve_part1(&reports));
+    println!("Part 2: {}", solve_part2(&reports));
+}
 
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+const BAD_INPUT: &str = "Every report must be a line of whole numbers";
 
-    let mut safe_count = 0;
+// None when anything isnt a number, those used to be dropped from the report
+fn parse_reports(input: &str) -> Option<Vec<Vec<i32>>> {
+    input
+        .lines()
+        .map(|line| line.split_whitespace().map(|n| n.parse().ok()).collect())
+        .collect()
+}
 
-    for line in reader.lines() {
-        let line = line?;
+fn solve_part1(reports: &[Vec<i32>]) -> usize {
+    reports.iter().filter(|levels| is_safe_report(levels)).count()
+}
 
-        let levels: Vec<i32> = line.split_whitespace().filter_map(|n| n.parse::<i32>().ok()).collect();
+fn solve_part2(reports: &[Vec<i32>]) -> usize {
+    reports
+        .iter()
+        .filter(|levels| is_safe_report(levels) || can_be_safe_with_removal(levels))
+        .count()
+}
 
-        if is_safe_report(&levels) || can_be_safe_with_removal(&levels) {
-            safe_count += 1;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9\n";
+
+    #[test]
+    fn example() {
+        let reports = parse_reports(EXAMPLE).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&reports), 2);
+        assert_eq!(solve_part2(&reports), 4);
     }
 
-    println!("Number of safe reports: {}", safe_count);
+    #[test]
+    fn stray_text_and_big_levels() {
+        assert!(parse_reports("1 2 x 3\n").is_none());
 
-    Ok(())
+        // the difference between neighbours overflowed i32
+        let reports = parse_reports("-2000000000 2000000000\n").unwrap();
+        assert_eq!(solve_part1(&reports), 0);
+        assert_eq!(solve_part2(&reports), 0);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(reports) = parse_reports(input) {
+                solve_part1(&reports);
+                solve_part2(&reports);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let reports = parse_reports(&input).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&reports), 279);
+        assert_eq!(solve_part2(&reports), 343);
+    }
 }
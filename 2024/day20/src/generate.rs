@@ -0,0 +1,92 @@
+use common::rng::Rng;
+
+// square racetrack `size` tiles across made of one winding corridor from S to E
+// a depth first carve gives a spanning tree, S sits at the root and E at the
+// deepest cell, and everything off that single path is walled back up
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+
+    // walls sit on even coordinates so the side has to be odd
+    let side = (size.max(5) - 1) / 2 * 2 + 1;
+    let cells = side / 2;
+
+    let mut parent = vec![vec![None; cells]; cells];
+    let mut visited = vec![vec![false; cells]; cells];
+    let mut stack = vec![(0, 0)];
+    visited[0][0] = true;
+    let mut deepest = ((0, 0), 0);
+
+    while let Some(&(r, c)) = stack.last() {
+        let mut options = Vec::new();
+        if r > 0 && !visited[r - 1][c] {
+            options.push((r - 1, c));
+        }
+        if r + 1 < cells && !visited[r + 1][c] {
+            options.push((r + 1, c));
+        }
+        if c > 0 && !visited[r][c - 1] {
+            options.push((r, c - 1));
+        }
+        if c + 1 < cells && !visited[r][c + 1] {
+            options.push((r, c + 1));
+        }
+
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let next = options[rng.below(options.len() as u64) as usize];
+        visited[next.0][next.1] = true;
+        parent[next.0][next.1] = Some((r, c));
+        stack.push(next);
+
+        if stack.len() > deepest.1 {
+            deepest = (next, stack.len());
+        }
+    }
+
+    // walk back from the deepest cell to the root, opening the corridor as we go
+    let mut grid = vec![vec!['#'; side]; side];
+    let end = deepest.0;
+    let mut cell = end;
+    grid[2 * cell.0 + 1][2 * cell.1 + 1] = '.';
+    while let Some(prev) = parent[cell.0][cell.1] {
+        grid[2 * prev.0 + 1][2 * prev.1 + 1] = '.';
+        grid[cell.0 + prev.0 + 1][cell.1 + prev.1 + 1] = '.';
+        cell = prev;
+    }
+
+    grid[1][1] = 'S';
+    grid[2 * end.0 + 1][2 * end.1 + 1] = 'E';
+
+    let mut out = String::with_capacity(side * (side + 1));
+    for row in grid {
+        out.extend(row);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BAD_MAZE, parse_maze, solve_part1, solve_part2};
+
+    #[test]
+    fn same_seed_same_track() {
+        assert_eq!(generate(41, 20), generate(41, 20));
+        assert_ne!(generate(41, 20), generate(41, 21));
+    }
+
+    #[test]
+    fn generated_track_parses_and_solves() {
+        let input = generate(141, 1);
+        assert_eq!(input.lines().count(), 141);
+        assert_eq!(input.matches('S').count(), 1);
+        assert_eq!(input.matches('E').count(), 1);
+        // every 2 step cheat is also a 20 step cheat
+        let maze = parse_maze(&input).expect(BAD_MAZE);
+        assert!(solve_part1(&maze) <= solve_part2(&maze));
+    }
+}
@@ -1,47 +1,77 @@
-use std::collections::{HashMap, VecDeque};
-use std::fs;
+use std::collections::VecDeque;
 
+use common::cells::CellMap;
+
+mod generate;
+
+// (row, col), signed so the cheat offsets can step off the grid
 type Pos = (i32, i32);
+type Maze = (Vec<Vec<char>>, Pos, Pos);
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
+    let input = common::input::load_or_generate("src/input.txt", generate::generate);
+    let maze = parse_maze(&input).expect(BAD_MAZE);
+    let part1 = solve_part1(&maze);
+    let part2 = solve_part2(&maze);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
 
-fn solve_part1(input: &str) -> usize {
-    let (grid, start, _end) = parse_maze(input);
-    let distances = bfs_distances(&grid, start);
-    count_cheats(&distances, 2, 100)
+const BAD_MAZE: &str = "The racetrack must be a rectangle with one S and one E";
+
+fn solve_part1((grid, start, _end): &Maze) -> usize {
+    let distances = bfs_distances(grid, *start);
+    count_cheats(common::par::threads(), &distances, 2, 100)
+}
+
+fn solve_part2((grid, start, _end): &Maze) -> usize {
+    let distances = bfs_distances(grid, *start);
+    count_cheats(common::par::threads(), &distances, 20, 100)
 }
 
-fn solve_part2(input: &str) -> usize {
-    let (grid, start, _end) = parse_maze(input);
-    let distances = bfs_distances(&grid, start);
-    count_cheats(&distances, 20, 100)
+// every track cell is a separate cheat start, split between `threads` threads
+fn count_cheats(
+    threads: usize,
+    distances: &CellMap<Option<usize>>,
+    max_cheat_dist: i32,
+    min_saving: usize,
+) -> usize {
+    let track: Vec<(Pos, usize)> = distances
+        .iter()
+        .filter_map(|((r, c), &dist)| Some(((r as i32, c as i32), dist?)))
+        .collect();
+    common::par::map_with(threads, &track, |&(pos, dist_start)| {
+        cheats_from(distances, pos, dist_start, max_cheat_dist, min_saving)
+    })
+    .into_iter()
+    .sum()
 }
 
-fn count_cheats(distances: &HashMap<Pos, usize>, max_cheat_dist: i32, min_saving: usize) -> usize {
+fn cheats_from(
+    distances: &CellMap<Option<usize>>,
+    pos: Pos,
+    dist_start: usize,
+    max_cheat_dist: i32,
+    min_saving: usize,
+) -> usize {
     let mut count = 0;
 
-    for (&pos, &dist_start) in distances {
-        // enumerate all positions within manhattan distance of max_cheat_dist
-        for dy in -max_cheat_dist..=max_cheat_dist {
-            let remaining = max_cheat_dist - dy.abs();
-            for dx in -remaining..=remaining {
-                let cheat_end = (pos.0 + dx, pos.1 + dy);
-
-                if let Some(&dist_end) = distances.get(&cheat_end) {
-                    let cheat_cost = dx.abs() + dy.abs();
-
-                    // can only cheat forward in time
-                    if dist_end > dist_start {
-                        let time_saved = dist_end - dist_start - cheat_cost as usize;
-                        if time_saved >= min_saving {
-                            count += 1;
-                        }
+    // enumerate all positions within manhattan distance of max_cheat_dist
+    for dy in -max_cheat_dist..=max_cheat_dist {
+        let remaining = max_cheat_dist - dy.abs();
+        for dx in -remaining..=remaining {
+            let cheat_end = (pos.0 + dy, pos.1 + dx);
+
+            // walls and cells off the grid both read as no distance
+            if let Some(&Some(dist_end)) = distances.get(cheat_end) {
+                let cheat_cost = dx.abs() + dy.abs();
+
+                // can only cheat forward in time, and off a single track
+                // the cheat can also take longer than just walking there
+                if dist_end > dist_start {
+                    let time_saved = (dist_end - dist_start).checked_sub(cheat_cost as usize);
+                    if time_saved.is_some_and(|saved| saved >= min_saving) {
+                        count += 1;
                     }
                 }
             }
@@ -51,25 +81,25 @@ fn count_cheats(distances: &HashMap<Pos, usize>, max_cheat_dist: i32, min_saving
     count
 }
 
-fn bfs_distances(grid: &[Vec<char>], start: Pos) -> HashMap<Pos, usize> {
-    let mut distances = HashMap::new();
+fn bfs_distances(grid: &[Vec<char>], start: Pos) -> CellMap<Option<usize>> {
+    let mut distances = CellMap::new(grid.len(), grid[0].len(), None);
     let mut queue = VecDeque::new();
 
     queue.push_back((start, 0));
-    distances.insert(start, 0);
+    distances[start] = Some(0);
 
     while let Some((pos, dist)) = queue.pop_front() {
         // try all 4 directions
-        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
-            let next = (pos.0 + dx, pos.1 + dy);
+        for (dr, dc) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let next = (pos.0 + dr, pos.1 + dc);
 
-            // check bounds and if its track
-            if next.1 >= 0 && next.1 < grid.len() as i32
-                && next.0 >= 0 && next.0 < grid[0].len() as i32 {
-                let cell = grid[next.1 as usize][next.0 as usize];
+            // an unvisited cell inside the grid, anything off the edge reads as None
+            if distances.get(next) == Some(&None) {
+                let cell = grid[next.0 as usize][next.1 as usize];
 
-                if (cell == '.' || cell == 'E') && !distances.contains_key(&next) {
-                    distances.insert(next, dist + 1);
+                // check if its track
+                if cell == '.' || cell == 'E' {
+                    distances[next] = Some(dist + 1);
                     queue.push_back((next, dist + 1));
                 }
             }
@@ -79,21 +109,95 @@ fn bfs_distances(grid: &[Vec<char>], start: Pos) -> HashMap<Pos, usize> {
     distances
 }
 
-fn parse_maze(input: &str) -> (Vec<Vec<char>>, Pos, Pos) {
+// None for a racetrack the bfs cant index, it sizes the distances off the first row
+fn parse_maze(input: &str) -> Option<Maze> {
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    let width = grid.first()?.len();
+    if grid.iter().any(|row| row.len() != width) {
+        return None;
+    }
 
-    let mut start = (0, 0);
-    let mut end = (0, 0);
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
 
-    for (y, row) in grid.iter().enumerate() {
-        for (x, &cell) in row.iter().enumerate() {
+    for (r, row) in grid.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
             if cell == 'S' {
-                start = (x as i32, y as i32);
+                starts.push((r as i32, c as i32));
             } else if cell == 'E' {
-                end = (x as i32, y as i32);
+                ends.push((r as i32, c as i32));
             }
         }
     }
 
-    (grid, start, end)
+    let (&[start], &[end]) = (&starts[..], &ends[..]) else {
+        return None;
+    };
+    Some((grid, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+";
+
+    #[test]
+    fn example() {
+        let (grid, start, _end) = parse_maze(EXAMPLE).expect(BAD_MAZE);
+        let distances = bfs_distances(&grid, start);
+        // the counts cant depend on how the track is split up
+        for threads in [1, 2, 3, 8] {
+            assert_eq!(count_cheats(threads, &distances, 2, 64), 1);
+            assert_eq!(count_cheats(threads, &distances, 2, 20), 5);
+            assert_eq!(count_cheats(threads, &distances, 20, 76), 3);
+            assert_eq!(count_cheats(threads, &distances, 20, 72), 29);
+        }
+    }
+
+    #[test]
+    fn open_floors_and_odd_shapes() {
+        // crashes found by fuzzing, the grid was sized off grid[0] and on an open
+        // floor a cheat that took longer than walking underflowed the time saved
+        assert!(parse_maze("").is_none());
+        assert!(parse_maze("S.E\n.").is_none());
+        assert!(parse_maze("S..\n...").is_none());
+        let (grid, start, _end) = parse_maze("S...\n....\n...E\n").expect(BAD_MAZE);
+        let distances = bfs_distances(&grid, start);
+        assert_eq!(count_cheats(1, &distances, 20, 1), 0);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 2_000, |input| {
+            if let Some(maze) = parse_maze(input) {
+                solve_part1(&maze);
+                solve_part2(&maze);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let maze = parse_maze(&input).expect(BAD_MAZE);
+        assert_eq!(solve_part1(&maze), 1507);
+        assert_eq!(solve_part2(&maze), 1037936);
+    }
 }
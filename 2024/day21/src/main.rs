@@ -1,9 +1,27 @@
 use std::collections::HashMap;
 
+const BAD_CODES: &str = "Every line must be a door code, three digits and an A";
+
+// each code with its numeric part, only the puzzle's shape of code is let through
+// so every key is on the keypad and the complexities stay well inside a usize
+fn parse_codes(input: &str) -> Option<Vec<(&str, usize)>> {
+    input
+        .lines()
+        .map(|code| {
+            let digits = code.strip_suffix('A')?;
+            if digits.len() != 3 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some((code, digits.parse().ok()?))
+        })
+        .collect()
+}
+
 fn main() {
-    let input = std::fs::read_to_string("src/input.txt").unwrap();
-    println!("Part 1: {}", solve(&input, 2));
-    println!("Part 2: {}", solve(&input, 25));
+    let input = common::input::load("src/input.txt");
+    let codes = parse_codes(&input).expect(BAD_CODES);
+    println!("Part 1: {}", solve(&codes, 2));
+    println!("Part 2: {}", solve(&codes, 25));
 }
 
 fn get_numeric_positions() -> HashMap<char, (i32, i32)> {
@@ -117,15 +135,14 @@ fn sequence_cost(
     total_cost
 }
 
-fn solve(input: &str, num_directional_robots: usize) -> usize {
+fn solve(codes: &[(&str, usize)], num_directional_robots: usize) -> usize {
     let numeric_pos = get_numeric_positions();
     let numeric_gap = (3, 0);
     let mut cache = HashMap::new();
 
-    let codes: Vec<&str> = input.lines().collect();
     let mut total_complexity = 0;
 
-    for code in codes {
+    for &(code, numeric_part) in codes {
         let mut current = 'A';
         let mut total_length = 0;
 
@@ -145,7 +162,6 @@ fn solve(input: &str, num_directional_robots: usize) -> usize {
             current = target;
         }
 
-        let numeric_part: usize = code[..code.len() - 1].parse().unwrap();
         let complexity = total_length * numeric_part;
 
         total_complexity += complexity;
@@ -153,3 +169,53 @@ fn solve(input: &str, num_directional_robots: usize) -> usize {
 
     total_complexity
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+029A
+980A
+179A
+456A
+379A
+";
+
+    #[test]
+    fn example() {
+        let codes = parse_codes(EXAMPLE).expect(BAD_CODES);
+        assert_eq!(solve(&codes, 2), 126384);
+    }
+
+    #[test]
+    fn codes_off_the_keypad() {
+        // crashes found by fuzzing, an empty line, a code without its A and a key that
+        // isnt on the keypad all panicked, and a long code's complexity overflowed
+        assert!(parse_codes("\n").is_none());
+        assert!(parse_codes("4").is_none());
+        assert!(parse_codes("A").is_none());
+        assert!(parse_codes("92016316A").is_none());
+        // str::parse takes a leading +, which isnt a key either
+        assert!(parse_codes("+12A").is_none());
+        assert_eq!(parse_codes("029A\n"), Some(vec![("029A", 29)]));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(codes) = parse_codes(input) {
+                solve(&codes, 2);
+                solve(&codes, 25);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let codes = parse_codes(&input).expect(BAD_CODES);
+        assert_eq!(solve(&codes, 2), 138764);
+        assert_eq!(solve(&codes, 25), 169137886514152);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 fn main() {
-    let input = std::fs::read_to_string("src/input.txt").unwrap();
+    let input = common::input::load("src/input.txt");
     println!("Part 1: {}", solve(&input, 2));
     println!("Part 2: {}", solve(&input, 25));
 }
@@ -1,11 +1,18 @@
-use std::fs;
-
 fn main() {
     // Read the entire file into memory
-    let file_content = fs::read("src/input.txt").expect("Failed to read file");
+    let file_content = common::input::load("src/input.txt").into_bytes();
+    let (accumulator, active_accumulator) = scan(&file_content);
+
+    // Print the final results
+    println!("Part 1: {}", accumulator);
+    println!("Part 2: {}", active_accumulator);
+}
 
+/// Sums every valid mul(x,y) in the memory, returns (all of them, only the enabled ones)
+fn scan(file_content: &[u8]) -> (i64, i64) {
     let mut curr_char_index = 0;
-    let mut accumulator: i64 = 0;
+    let mut accumulator: i64 = 0; // every mul, part 1
+    let mut active_accumulator: i64 = 0; // only muls while enabled, part 2
     let mut active = true;
 
     let file_len = file_content.len();
@@ -31,13 +38,15 @@ fn main() {
         if remaining.starts_with(b"mul(") {
             let after_mul = &remaining[4..];
             if let Some((x_str, rest)) = parse_number(after_mul) {
-                if let Some((y_str, rest)) = parse_number(&rest[1..]) { // Skip the ',' after first number
+                // rest can be empty or hold anything at all here, so the ',' has to be checked
+                if let Some((y_str, rest)) = rest.strip_prefix(b",").and_then(parse_number) {
                     if rest.starts_with(b")") {
                         // Convert numbers and compute
                         if let (Ok(x), Ok(y)) = (x_str.parse::<i64>(), y_str.parse::<i64>()) {
                             let result = x * y;
+                            accumulator += result;
                             if active {
-                                accumulator += result;
+                                active_accumulator += result;
                             }
                         }
                         curr_char_index += 4 + x_str.len() + 1 + y_str.len() + 1; // Advance past "mul(x,y)"
@@ -51,11 +60,10 @@ fn main() {
         curr_char_index += 1;
     }
 
-    // Print the final result
-    println!("{}", accumulator);
+    (accumulator, active_accumulator)
 }
 
-/// Parses a number from the start of the given byte slice and returns the number
+/// Parses a 1-3 digit number from the start of the given byte slice and returns the number
 /// as a string and the remaining slice 
 fn parse_number(data: &[u8]) -> Option<(&str, &[u8])> {
     let len = data.len();
@@ -66,9 +74,39 @@ fn parse_number(data: &[u8]) -> Option<(&str, &[u8])> {
         end_index += 1;
     }
 
-    if end_index > 0 {
+    // the puzzle only allows up to 3 digits, longer runs arent valid instructions
+    if (1..=3).contains(&end_index) {
         Some((std::str::from_utf8(&data[..end_index]).ok()?, &data[end_index..]))
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn example() {
+        assert_eq!(scan(EXAMPLE.as_bytes()), (161, 48));
+    }
+
+    #[test]
+    fn truncated_and_malformed_muls() {
+        // crashes found by fuzzing, a number right at the end used to slice past it
+        assert_eq!(scan(b"mul(12"), (0, 0));
+        assert_eq!(scan(b"mul(1,"), (0, 0));
+        // the separator has to be a comma and numbers are at most 3 digits
+        assert_eq!(scan(b"mul(2x3)mul(1234,2)mul(2,3)"), (6, 6));
+    }
+
+    #[test]
+    fn fuzz_scan() {
+        common::fuzz::check(&[EXAMPLE, "mul(123,4)do()mul(5,67)"], 20_000, |input| {
+            scan(input.as_bytes());
+        });
+    }
+}
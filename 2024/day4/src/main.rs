@@ -1,11 +1,9 @@
-use std::fs;
-use std::collections::HashMap;
+use common::cells::CellMap;
 
 fn count_word(grid: &[Vec<char>], word: &str) -> usize {
     let rows = grid.len();
     let cols = grid[0].len();
     let word_chars: Vec<char> = word.chars().collect();
-    let word_len = word_chars.len();
     let mut count = 0;
 
     let directions = [
@@ -23,7 +21,7 @@ fn count_word(grid: &[Vec<char>], word: &str) -> usize {
         for col in 0..cols {
             for &(dx, dy) in &directions {
                 let mut matched = true;
-                for i in 0..word_len {
+                for (i, &ch) in word_chars.iter().enumerate() {
                     let nx = row as isize + dx * i as isize;
                     let ny = col as isize + dy * i as isize;
 
@@ -32,7 +30,7 @@ fn count_word(grid: &[Vec<char>], word: &str) -> usize {
                         break;
                     }
 
-                    if grid[nx as usize][ny as usize] != word_chars[i] {
+                    if grid[nx as usize][ny as usize] != ch {
                         matched = false;
                         break;
                     }
@@ -46,7 +44,7 @@ fn count_word(grid: &[Vec<char>], word: &str) -> usize {
     count
 }
 
-fn is_cross(pos: (i32, i32), lut: &HashMap<(i32, i32), char>) -> bool {
+fn is_cross(pos: (i32, i32), lut: &CellMap<char>) -> bool {
     let (x, y) = pos;
 
     let diagonals = [
@@ -56,46 +54,111 @@ fn is_cross(pos: (i32, i32), lut: &HashMap<(i32, i32), char>) -> bool {
         (x + 1, y + 1),
     ];
 
-    if diagonals.iter().any(|&n| !lut.contains_key(&n)) {
+    // the lut is row major so its looked up as (y, x), off the edge reads as None
+    let chars: Option<String> = diagonals
+        .iter()
+        .map(|&(nx, ny)| lut.get((ny, nx)).copied())
+        .collect();
+    let Some(diag_str) = chars else {
         return false;
-    }
-
-    let chars: Vec<char> = diagonals.iter().map(|&n| lut.get(&n).unwrap()).cloned().collect();
+    };
 
     let valid_patters = ["MSMS", "SMSM", "MMSS", "SSMM"];
-    let diag_str: String = chars.iter().collect();
 
-    valid_patters.contains(&&diag_str.as_str())
+    valid_patters.contains(&diag_str.as_str())
 }
 
 fn count_x_mas(grid: &[Vec<char>]) -> usize {
-    let mut lut: HashMap<(i32, i32), char> = HashMap::new();
+    // only XMAS letters go in, anything else stays '.' and never matches a pattern
+    let mut lut = CellMap::new(grid.len(), grid[0].len(), '.');
 
     for (y, row) in grid.iter().enumerate() {
         for (x, &c) in row.iter().enumerate() {
             if c == 'X' || c == 'M' || c == 'A' || c == 'S' {
-                lut.insert((x as i32, y as i32), c);
+                lut[(y, x)] = c;
             }
         }
     }
     
     let mut count = 0;
 
-    for (&(x, y), &c) in &lut {
-        if c == 'A' && is_cross((x, y), &lut) {
+    for ((y, x), &c) in lut.iter() {
+        if c == 'A' && is_cross((x as i32, y as i32), &lut) {
             count += 1;
         }
     }
 
     count
 }
-fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input file");
+
+const BAD_GRID: &str = "The word search must be a rectangle of letters";
+
+// None unless it's a rectangle, both searches size everything off the first row
+fn parse_grid(input: &str) -> Option<Vec<Vec<char>>> {
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    let width = grid.first()?.len();
+    grid.iter().all(|row| row.len() == width).then_some(grid)
+}
+
+fn main() {
+    let input = common::input::load("src/input.txt");
+    let grid = parse_grid(&input).expect(BAD_GRID);
     let word = "XMAS";
     let occurrences = count_word(&grid, word);
-    println!("The Word '{}' appears {} times in the search", word, occurrences);
+    println!("Part 1: {}", occurrences);
 
     let xmas_occurrences = count_x_mas(&grid);
-    println!("The X-MAS Pattern appears {} times in the search", xmas_occurrences);
+    println!("Part 2: {}", xmas_occurrences);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    #[test]
+    fn example() {
+        let grid = parse_grid(EXAMPLE).expect(BAD_GRID);
+        assert_eq!(count_word(&grid, "XMAS"), 18);
+        assert_eq!(count_x_mas(&grid), 9);
+    }
+
+    #[test]
+    fn empty_and_ragged_grids() {
+        // crashes found by fuzzing, both searches read grid[0] and took every
+        // row to be as wide as it
+        assert!(parse_grid("").is_none());
+        assert!(parse_grid("\nM").is_none());
+        assert!(parse_grid("XMAS\nXM\n").is_none());
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(grid) = parse_grid(input) {
+                count_word(&grid, "XMAS");
+                count_x_mas(&grid);
+            }
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let grid = parse_grid(&input).expect(BAD_GRID);
+        assert_eq!(count_word(&grid, "XMAS"), 2545);
+        assert_eq!(count_x_mas(&grid), 1886);
+    }
 }
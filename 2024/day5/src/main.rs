@@ -1,102 +1,167 @@
-use std::fs;
-use std::iter::from_fn;
+use std::collections::HashMap;
 
-fn parse_input(input: &str) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
-    let mut rules = vec![Vec::new(); 100];
-    let mut updates = Vec::new();
+use common::graph::Graph;
 
-    let mut line_iter = input.lines().into_iter();
+// pages that have to come after each page
+type Rules = HashMap<usize, Vec<usize>>;
+type PrintQueue = (Rules, Vec<Vec<usize>>);
 
-    while let Some(line) = line_iter.next() {
+const BAD_INPUT: &str =
+    "Expected page|page rules, a blank line and comma separated updates the rules can order";
+
+// None unless every rule and page is a number, and the rules between each update's pages
+// don't loop, part 2 sorts every update by them
+fn parse_input(input: &str) -> Option<PrintQueue> {
+    let mut rules = Rules::new();
+    let mut line_iter = input.lines();
+
+    for line in line_iter.by_ref() {
         if line.is_empty() {
             break;
         }
-        let mut parts = line.split('|');
-        let x: usize = parts.next().unwrap().parse().unwrap();
-        let y: usize = parts.next().unwrap().parse().unwrap();
-        rules[x].push(y);
+        let (x, y) = line.split_once('|')?;
+        rules.entry(x.parse().ok()?).or_default().push(y.parse().ok()?);
     }
 
-    for line in line_iter {
-        if !line.is_empty() {
-            let mut bytes = line.bytes();
-            updates.push(from_fn(|| {
-                let mut num = Vec::new();
-                while let Some(b) = bytes.next() {
-                    if b == b',' {
-                        break;
-                    }
-                    num.push(b);
-                }
-                if num.is_empty() {
-                    None
-                } else {
-                    Some(String::from_utf8(num).unwrap().parse().unwrap())
-                }
-            }).collect());
-        }
-    }
+    // a non empty line always has at least one page, so no update is empty
+    let updates: Vec<Vec<usize>> = line_iter
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split(',').map(|page| page.parse().ok()).collect())
+        .collect::<Option<_>>()?;
 
-    (rules, updates)
+    updates
+        .iter()
+        .all(|update| page_order(update, &rules).is_some())
+        .then_some((rules, updates))
 }
 
-fn check_update(update: &Vec<usize>, rules: &Vec<Vec<usize>>) -> Option<usize> {
-    for i in 0..update.len() - 1 {
-        if !rules[update[i]].contains(&update[i + 1]) {
-            return None;
-        }
-    }
-    Some(update[update.len() / 2])
+fn check_update(update: &[usize], rules: &Rules) -> Option<usize> {
+    let in_order = update
+        .windows(2)
+        .all(|pair| rules.get(&pair[0]).is_some_and(|next| next.contains(&pair[1])));
+    in_order.then(|| update[update.len() / 2])
 }
 
-fn find_next_page(update: &Vec<usize>, rules: &Vec<Vec<usize>>) -> Option<usize> {
-    for i in 0..update.len() {
-        let rule = &rules[update[i]];
-        let mut found = true;
-        for n in 0..update.len() {
-            if n == i {
-                continue;
-            }
+// indices into the update in the order the rules put them, None if the rules loop
+fn page_order(update: &[usize], rules: &Rules) -> Option<Vec<usize>> {
+    // only the rules between pages in this update matter, the full rule set has cycles
+    let edges = update.iter().enumerate().flat_map(|(i, page)| {
+        rules
+            .get(page)
+            .into_iter()
+            .flatten()
+            .filter_map(|next| update.iter().position(|p| p == next))
+            .map(move |j| (i, j))
+    });
 
-            if rule.contains(&update[n]) {
-                found = false;
-                break;
-            }
-        }
-        if found {
-            return Some(i);
-        }
-    }
-    None
+    Graph::from_edges(update.len(), edges).topological_sort().ok()
 }
 
-fn reorder_update(update: &mut Vec<usize>, rules: &Vec<Vec<usize>>) -> usize {
-    let mut result = 0;
+fn reorder_update(update: &[usize], rules: &Rules) -> usize {
+    let order = page_order(update, rules).expect("rules should order every update");
+    update[order[update.len() / 2]]
+}
 
-    for _ in 0..=update.len() / 2 {
-        if let Some(page_index) = find_next_page(update, rules) {
-            result = update.remove(page_index);
-        }
-    }
-    result
+fn solve_part1((rules, updates): &PrintQueue) -> usize {
+    updates
+        .iter()
+        .filter_map(|update| check_update(update, rules))
+        .sum()
+}
+
+fn solve_part2((rules, updates): &PrintQueue) -> usize {
+    updates
+        .iter()
+        .filter(|update| check_update(update, rules).is_none())
+        .map(|update| reorder_update(update, rules))
+        .sum()
 }
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input file");
-    let (rules, mut updates) = parse_input(&input);
+    let input = common::input::load("src/input.txt");
+    let queue = parse_input(&input).expect(BAD_INPUT);
 
-    let part1_result: usize = updates
-        .iter()
-        .filter_map(|update| check_update(update, &rules))
-        .sum();
+    println!("Part 1: {}", solve_part1(&queue));
+    println!("Part 2: {}", solve_part2(&queue));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    #[test]
+    fn example() {
+        let queue = parse_input(EXAMPLE).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&queue), 143);
+        assert_eq!(solve_part2(&queue), 123);
+    }
+
+    #[test]
+    fn stray_text_big_pages_and_empty_updates() {
+        // crashes found by fuzzing, every rule and page was unwrapped, the rules
+        // were a table of 100 pages and an update with no pages underflowed
+        assert!(parse_input("7").is_none());
+        assert!(parse_input("\n|").is_none());
+        assert!(parse_input("\n,").is_none());
+
+        let queue = parse_input("971|1\n\n971,1\n1,5,971\n").expect(BAD_INPUT);
+        assert_eq!(solve_part1(&queue), 1);
+        assert_eq!(solve_part2(&queue), 971);
+    }
 
-    println!("The sum of middle pages of correctly ordered updates is: {}", part1_result);
+    #[test]
+    fn rules_that_loop() {
+        // the sort expected the rules between an update's pages never to loop
+        assert!(parse_input("1|2\n2|3\n3|1\n\n1,3,2\n").is_none());
+        assert!(parse_input("1|1\n\n1,2\n").is_none());
+    }
 
-    let part2_result: usize = updates
-        .iter_mut()
-        .filter(|update| check_update(update, &rules).is_none())
-        .map(|update| reorder_update(update, &rules))
-        .sum();
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(queue) = parse_input(input) {
+                solve_part1(&queue);
+                solve_part2(&queue);
+            }
+        });
+    }
 
-    println!("The sum of middle pages of reordered updates is: {}", part2_result);
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let queue = parse_input(&input).expect(BAD_INPUT);
+        assert_eq!(solve_part1(&queue), 5248);
+        assert_eq!(solve_part2(&queue), 4507);
+    }
 }
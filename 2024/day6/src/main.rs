@@ -1,39 +1,60 @@
 use std::collections::HashSet;
 
+use common::sim::{Colour, Frame, Simulation};
+
+const BAD_GRID: &str = "The map must be a rectangle with one ^ guard who walks off it";
+
+#[derive(Clone)]
 struct Grid {
     tiles: Vec<Vec<char>>,
     width: usize,
     height: usize,
+    guard: (usize, usize),
 }
 
 impl Grid {
-    fn new(input: &str) -> Self {
+    // None unless it's a rectangle with exactly one guard who walks off the map
+    // without any extra obstacle, part 1 would follow a guard round a loop forever
+    fn parse(input: &str) -> Option<Self> {
         let tiles: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
         let height = tiles.len();
-        let width = tiles[0].len();
+        let width = tiles.first()?.len();
+        if tiles.iter().any(|row| row.len() != width) {
+            return None;
+        }
 
-        Self {
+        let mut guards = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .filter(|&(row, col)| tiles[row][col] == '^');
+        let guard = guards.next()?;
+        if guards.next().is_some() {
+            return None;
+        }
+
+        let grid = Self {
             tiles,
             width,
             height,
-        }
+            guard,
+        };
+        (!gets_in_loop(&grid, guard, Direction::Up, None)).then_some(grid)
     }
 
-    fn get_guard_position(&self) -> (usize, usize) {
-        for row in 0..self.width {
-            for col in 0..self.height {
-                if self.tiles[row][col] == '^' {
-                    return (row, col);
-                }
-            }
-        }
-        panic!("guard no found in grid")
+    fn get_next_pos(
+        &self,
+        guard: (usize, usize),
+        direction: &mut Direction,
+    ) -> Option<(usize, usize)> {
+        self.get_next_pos_with(guard, direction, None)
     }
 
-    fn get_next_pos(
+    // same as get_next_pos with one extra obstacle that isnt in the tiles,
+    // so the loop checks can share the grid instead of each editing a copy
+    fn get_next_pos_with(
         &self,
         (guard_row, guard_col): (usize, usize),
         direction: &mut Direction,
+        obstacle: Option<(usize, usize)>,
     ) -> Option<(usize, usize)> {
         let (next_row, next_col) = match direction {
             Direction::Up => (guard_row.checked_sub(1)?, guard_col),
@@ -44,7 +65,7 @@ impl Grid {
 
         let char = self.tiles.get(next_row).and_then(|row| row.get(next_col))?;
 
-        if char == &'#' {
+        if char == &'#' || obstacle == Some((next_row, next_col)) {
             direction.turn_right();
             return Some((guard_row, guard_col));
         }
@@ -72,86 +93,199 @@ impl Direction {
     }
 }
 
-#[allow(dead_code)]
-pub fn part_one() -> usize {
-    let input: &str = include_str!("input.txt");
+// the part 1 walk, one move or turn per step so it can be watched with --play
+struct GuardWalk {
+    grid: Grid,
+    guard: (usize, usize),
+    direction: Direction,
+    visited: HashSet<(usize, usize)>,
+}
 
-    let grid = Grid::new(input);
-    let (mut guard_row, mut guard_col) = grid.get_guard_position();
-    let mut direction = Direction::Up; // UP
+impl GuardWalk {
+    fn new(grid: Grid) -> Self {
+        let guard = grid.guard;
+        Self {
+            grid,
+            guard,
+            direction: Direction::Up,
+            visited: HashSet::from([guard]),
+        }
+    }
+}
 
-    let mut visited = HashSet::new();
-    visited.insert((guard_row, guard_col));
+impl Simulation for GuardWalk {
+    fn step(&mut self) -> bool {
+        match self.grid.get_next_pos(self.guard, &mut self.direction) {
+            Some(next) => {
+                self.guard = next;
+                self.visited.insert(next);
+                true
+            }
+            None => false,
+        }
+    }
 
-    while let Some((next_row, next_col)) = grid.get_next_pos((guard_row, guard_col), &mut direction)
-    {
-        guard_row = next_row;
-        guard_col = next_col;
+    fn render(&self) -> Frame {
+        let mut frame = Frame::new(self.grid.width, self.grid.height, '.');
+        for (row, tiles) in self.grid.tiles.iter().enumerate() {
+            for (col, &tile) in tiles.iter().enumerate() {
+                if tile == '#' {
+                    frame.set(row, col, '#', None);
+                }
+            }
+        }
+        for &(row, col) in &self.visited {
+            frame.set(row, col, 'X', Some(Colour::Yellow));
+        }
 
-        visited.insert((guard_row, guard_col));
+        let arrow = match self.direction {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        };
+        frame.set(self.guard.0, self.guard.1, arrow, Some(Colour::Red));
+        frame.set_status(format!("visited {}", self.visited.len()));
+        frame
     }
+}
 
-    visited.len()
+fn part_one(grid: &Grid) -> usize {
+    let mut walk = GuardWalk::new(grid.clone());
+    while walk.step() {}
+    walk.visited.len()
 }
 
-#[allow(dead_code)]
-pub fn part_two() -> usize {
-    let input: &str = include_str!("input.txt");
+fn part_two(grid: &Grid) -> usize {
+    part_two_with(common::par::threads(), grid)
+}
 
-    let mut grid = Grid::new(input);
-    let (mut guard_row, mut guard_col) = grid.get_guard_position();
+// part two with the candidates spread over `threads` threads
+fn part_two_with(threads: usize, grid: &Grid) -> usize {
+    let (mut guard_row, mut guard_col) = grid.guard;
     let mut direction = Direction::Up;
 
+    // every spot on the walk gets tried as an obstacle, placed the first time the guard
+    // is about to step onto it (putting it there any later would change the earlier walk)
     let mut visited = HashSet::new();
-    let mut count = 0;
+    let mut candidates = Vec::new();
 
     while let Some((next_row, next_col)) = grid.get_next_pos((guard_row, guard_col), &mut direction)
     {
         visited.insert((guard_row, guard_col));
 
         if !visited.contains(&(next_row, next_col)) {
-            grid.tiles[next_row][next_col] = '#';
-            if gets_in_loop(&grid, (guard_row, guard_col), direction) {
-                count += 1;
-            }
-            grid.tiles[next_row][next_col] = '.';
+            candidates.push(((guard_row, guard_col), direction, (next_row, next_col)));
         }
 
         (guard_row, guard_col) = (next_row, next_col);
     }
 
-    count
+    // the candidates dont depend on each other, `--features parallel` checks them on all cores
+    common::par::map_with(threads, &candidates, |&(start, direction, obstacle)| {
+        gets_in_loop(grid, start, direction, Some(obstacle))
+    })
+    .into_iter()
+    .filter(|&looped| looped)
+    .count()
 }
 
 fn gets_in_loop(
     grid: &Grid,
     (start_row, start_col): (usize, usize),
     start_direction: Direction,
+    obstacle: Option<(usize, usize)>,
 ) -> bool {
-    // only need to keep track of the times the guard turned
-    // if the guard made the same turn at the same obstacle twice then we have a cycle
-    let mut visited_obstacles: Vec<(usize, usize, Direction)> = Vec::new();
+    // only need to look at the states where the guard turns
+    // if the guard ever makes the same turn at the same obstacle twice then we have a cycle
+    let next_turn = |&(pos, direction): &((usize, usize), Direction)| {
+        let (mut pos, mut direction) = (pos, direction);
+        loop {
+            let next = grid.get_next_pos_with(pos, &mut direction, obstacle)?;
+            if next == pos {
+                return Some((pos, direction));
+            }
+            pos = next;
+        }
+    };
 
-    let mut direction = start_direction;
-    let (mut guard_row, mut guard_col) = (start_row, start_col);
+    common::cycle::hashed(((start_row, start_col), start_direction), next_turn).is_some()
+}
 
-    while let Some((next_row, next_col)) = grid.get_next_pos((guard_row, guard_col), &mut direction)
-    {
-        if (guard_row, guard_col) == (next_row, next_col) {
-            if visited_obstacles.contains(&(guard_row, guard_col, direction)) {
-                return true;
-            }
+fn main() {
+    let input = common::input::load("src/input.txt");
+    let grid = Grid::parse(&input).expect(BAD_GRID);
+    println!("Part 1: {}", part_one(&grid));
+    println!("Part 2: {}", part_two(&grid));
 
-            visited_obstacles.push((guard_row, guard_col, direction));
+    if common::sim::requested() {
+        common::sim::play(&mut GuardWalk::new(grid));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    #[test]
+    fn example() {
+        let grid = Grid::parse(EXAMPLE).expect(BAD_GRID);
+        assert_eq!(part_one(&grid), 41);
+        assert_eq!(part_two(&grid), 6);
+    }
+
+    #[test]
+    fn any_thread_count_finds_the_same_loops() {
+        let grid = Grid::parse(EXAMPLE).expect(BAD_GRID);
+        for threads in [2, 3, 8] {
+            assert_eq!(part_two_with(threads, &grid), part_two_with(1, &grid));
         }
+    }
 
-        (guard_row, guard_col) = (next_row, next_col);
+    #[test]
+    fn odd_maps_and_trapped_guards() {
+        // crashes found by fuzzing, an empty map and no guard both panicked, and the
+        // guard search swapped width and height so a map wider than tall read past the end
+        assert!(Grid::parse("").is_none());
+        assert!(Grid::parse("..\n.#\n").is_none());
+        let grid = Grid::parse(".^").expect(BAD_GRID);
+        assert_eq!(part_one(&grid), 1);
+        assert_eq!(part_two(&grid), 0);
+
+        // fuzzing hung as well, a guard stuck in a loop never walks off so part 1 never ended
+        assert!(Grid::parse(".#.\n#^#\n.#.\n").is_none());
+        assert!(Grid::parse("^^\n..\n").is_none());
+        assert!(Grid::parse("^.\n.\n").is_none());
     }
 
-    false
-}
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            if let Some(grid) = Grid::parse(input) {
+                part_one(&grid);
+                part_two_with(1, &grid);
+            }
+        });
+    }
 
-fn main() {
-    println!("Part 1: {}", part_one());
-    println!("Part 2: {}", part_two());
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        let grid = Grid::parse(&input).expect(BAD_GRID);
+        assert_eq!(part_one(&grid), 4374);
+        assert_eq!(part_two(&grid), 1705);
+    }
 }
@@ -1,114 +1,166 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use common::num::{self, Arith, Signed};
 
-fn eval_expression1(nums: &[i64], ops: &[char]) -> i64 {
+fn eval_expression1(nums: &[Signed], ops: &[char], target: Signed) -> Signed {
+    let prune = only_grows(nums);
     let mut result = nums[0];
     for(i, &op) in ops.iter().enumerate() {
+        // with every number positive the result never comes back down,
+        // stopping here also keeps the products from overflowing
+        if prune && result > target {
+            break;
+        }
         match op {
-            '+' => result += nums[i + 1],
-            '*' => result *= nums[i + 1],
+            '+' => result = result.plus(nums[i + 1]),
+            '*' => result = result.times(nums[i + 1]),
             _ => unreachable!(),
         }
     }
     result
 }
 
-fn generate_operator_combinations1(nums: &[i64], target: i64) -> bool {
+fn generate_operator_combinations1(nums: &[Signed], target: Signed) -> bool {
     let num_ops = nums.len() - 1;
     // let ops = vec!['+', '*'];
 
     for bitmask in 0..(1 << num_ops) {
         let mut op_sequence = vec!['+'; num_ops];
-        for i in 0..num_ops {
+        for (i, op) in op_sequence.iter_mut().enumerate() {
             if (bitmask & (1 << i)) != 0 {
-                op_sequence[i] = '*';
+                *op = '*';
             }
         }
 
-        if eval_expression1(nums, &op_sequence) == target {
+        if eval_expression1(nums, &op_sequence, target) == target {
             return true;
         }
     }
     false
 }
 
-fn process_file1(filename: &str) -> i64 {
-    let path = Path::new(filename);
-    let file = File::open(path).expect("Unable to open file");
-    let reader = io::BufReader::new(file);
+fn solve_part1(input: &str) -> Signed {
     let mut total = 0;
 
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            let parts: Vec<&str> = line.split(':').collect();
-            if parts.len() != 2 {
-                continue;
-            }
-
-            let target: i64 = parts[0].trim().parse().expect("Invalid target value");
-            let nums: Vec<i64> = parts[1]
-                .trim()
-                .split_whitespace()
-                .map(|x| x.parse().expect("Invalid number"))
-                .collect();
-            
-            if generate_operator_combinations1(&nums, target) {
-                total += target;
-            }
+    for (target, nums) in parse_equations(input) {
+        if generate_operator_combinations1(&nums, target) {
+            total = total.plus(target);
         }
     }
     total
 }
 
-fn can_generate_result(curr_sum: i64, idx: usize, target: i64, nums: &[i64]) -> bool {
+// "190: 10 19", lines that dont read as a target and at least one number are skipped
+fn parse_equations(input: &str) -> Vec<(Signed, Vec<Signed>)> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let (target, nums) = line.split_once(':')?;
+            let target: Signed = target.trim().parse().ok()?;
+            let nums: Vec<Signed> = nums
+                .split_whitespace()
+                .map(|x| x.parse().ok())
+                .collect::<Option<_>>()?;
+            (!nums.is_empty()).then_some((target, nums))
+        })
+        .collect()
+}
+
+// a 0 can multiply the total back down and a negative one can take it down too,
+// with neither every operator only makes it bigger and going past the target is a dead end
+fn only_grows(nums: &[Signed]) -> bool {
+    nums.iter().all(|&n| n > 0)
+}
+
+fn can_generate_result(curr_sum: Signed, idx: usize, target: Signed, nums: &[Signed], prune: bool) -> bool {
     if idx == nums.len() {
         return curr_sum == target;
     }
-    
-    can_generate_result(curr_sum + nums[idx], idx + 1, target, nums) ||
-    can_generate_result(curr_sum * nums[idx], idx + 1, target, nums) ||
-    can_generate_result(curr_sum * 10_i64.pow(nums[idx].to_string().len() as u32) + nums[idx], idx + 1, target, nums)
+    // same as part 1
+    if prune && curr_sum > target {
+        return false;
+    }
+
+    let shift = (10 as Signed).pow(nums[idx].to_string().len() as u32);
+    can_generate_result(curr_sum.plus(nums[idx]), idx + 1, target, nums, prune) ||
+    can_generate_result(curr_sum.times(nums[idx]), idx + 1, target, nums, prune) ||
+    can_generate_result(curr_sum.times(shift).plus(nums[idx]), idx + 1, target, nums, prune)
 }
 
-fn calibration_result(equations: &[(i64, Vec<i64>)]) -> i64 {
-    equations.iter()
-        .filter(|(target, nums)| can_generate_result(nums[0], 1, *target, nums))
-        .map(|(target, _)| target)
-        .sum()
+// each equation is checked on its own, so they can go to different threads
+fn calibration_result(threads: usize, equations: &[(Signed, Vec<Signed>)]) -> Signed {
+    num::sum(common::par::map_with(threads, equations, |(target, nums)| {
+        let prune = only_grows(nums);
+        if can_generate_result(nums[0], 1, *target, nums, prune) { *target } else { Signed::ZERO }
+    }))
 }
 
-fn process_file2(filename: &str) -> i64 {
-    let path = Path::new(filename);
-    let file = File::open(path).expect("Unable to open file");
-    let reader = io::BufReader::new(file);
-    let mut equations = Vec::new();
-
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            let parts: Vec<&str> = line.split(':').collect();
-            if parts.len() != 2 {
-                continue;
-            }
-            
-            let target: i64 = parts[0].trim().parse().expect("Invalid target value");
-            let nums: Vec<i64> = parts[1]
-                .trim()
-                .split_whitespace()
-                .map(|x| x.parse().expect("Invalid number"))
-                .collect();
-            
-            equations.push((target, nums));
+fn solve_part2(input: &str) -> Signed {
+    calibration_result(common::par::threads(), &parse_equations(input))
+}
+
+fn main() {
+    let input = common::input::load("src/input.txt");
+    let part1 = num::checked_or_exit(|| solve_part1(&input));
+    let part2 = num::checked_or_exit(|| solve_part2(&input));
+    println!("Part 1: {}", part1);
+    println!("Part 2: {}", part2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 3749);
+        assert_eq!(solve_part2(EXAMPLE), 11387);
+    }
+
+    #[test]
+    fn any_thread_count_gives_the_same_total() {
+        let equations = parse_equations(EXAMPLE);
+        for threads in [2, 3, 8] {
+            assert_eq!(calibration_result(threads, &equations), calibration_result(1, &equations));
         }
     }
-    calibration_result(&equations)
-}
 
+    #[test]
+    fn zeros_can_bring_the_total_back_down() {
+        // going past 10 with 20 isnt a dead end when the next number is 0
+        assert_eq!(solve_part1("10: 20 0 10\n"), 10);
+        assert_eq!(solve_part2("10: 20 0 10\n"), 10);
+        assert_eq!(solve_part2("-3: 4 -7\n"), -3);
+    }
 
-fn main() {
-    let filename = "src/input.txt";
-    let total = process_file1(filename);
-    println!("Total Calibration Result Part 1: {}", total);
-    let total = process_file2(filename);
-    println!("Total Calibration Result Part 2: {}", total);
-}
\ No newline at end of file
+    #[test]
+    fn skips_bad_lines() {
+        // crashes found by fuzzing, an equation without numbers and a number that isnt one
+        assert_eq!(solve_part1("5:\n190: 10 19\n7: x\n"), 190);
+        assert_eq!(solve_part2("5:\n190: 10 19\n7: x\n"), 190);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part2(&input), 150077710195188);
+    }
+}
@@ -1,40 +1,47 @@
-use std::fs::File;
 use std::collections::{HashMap, HashSet};
-use std::io::{BufRead, BufReader};
 
-fn gcd(a: i64, b: i64) -> i64 {
-    if b == 0 {
-        a
-    } else {
-        gcd(b, a % b)
-    }
-}
+use common::geometry::{self, Point, Rect};
 
 fn main() {
-    let input = File::open("src/input.txt").expect("Unable to open file");
-    let reader = BufReader::new(input);
-
-    let input: Vec<String> = reader.lines().map(|x| x.unwrap()).collect();
-
-    let height = input.len();
+    let input = common::input::load("src/input.txt");
 
-    if height == 0 {
+    if input.lines().next().is_none() {
         println!("No input provided");
         return;
     }
-    let width = input[0].len();
 
-    let mut antennas: HashMap<char, Vec<(usize, usize)>> = HashMap::new();
-    for (r, line) in input.iter().enumerate() {
+    println!("Part 1: {}", solve_part1(&input));
+    println!("Part 2: {}", solve_part2(&input));
+}
+
+fn parse_input(input: &str) -> (Rect, HashMap<char, Vec<Point>>) {
+    let lines: Vec<&str> = input.lines().collect();
+    // ragged lines get the widest one as the width, so every antenna is on the map
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let bounds = Rect::grid(lines.len(), width);
+
+    let mut antennas: HashMap<char, Vec<Point>> = HashMap::new();
+    for (r, line) in lines.iter().enumerate() {
         for (c, ch) in line.chars().enumerate() {
             if ch != '.' {
-                antennas.entry(ch).or_default().push((r, c));
+                antennas.entry(ch).or_default().push((r as i64, c as i64));
             }
         }
     }
 
-    let mut antinodes = HashSet::new();
+    (bounds, antennas)
+}
+
+// every pair of same-frequency antennas, each pair once
+fn pairs(antennas: &HashMap<char, Vec<Point>>) -> impl Iterator<Item = (Point, Point)> + '_ {
+    antennas.values().flat_map(|coords| {
+        (0..coords.len()).flat_map(move |i| (i + 1..coords.len()).map(move |j| (coords[i], coords[j])))
+    })
+}
 
+fn solve_part1(input: &str) -> usize {
+    let (bounds, antennas) = parse_input(input);
+    let mut antinodes = HashSet::new();
 
     //---------------------------------------------
     // Reflections of A and B across each other
@@ -43,95 +50,87 @@ fn main() {
     // Reflection of A across B = (2r2 - r1, 2c2 - c1)
     // --------------------------------------------
 
-    for coords in antennas.values() {
-        for i in 0..coords.len() {
-            for j in i+1..coords.len() {
-                let (r1, c1) = coords[i];
-                let (r2, c2) = coords[j];
-
-                // Reflection of B across A
-                let ar = 2i64 * (r1 as i64) - (r2 as i64);
-                let ac = 2i64 * (c1 as i64) - (c2 as i64);
-                if ar >= 0 && ar < height as i64 && ac >= 0 && ac < width as i64 {
-                    antinodes.insert((ar as usize, ac as usize));
-                }
-
-                // Reflection of A across B
-                let br = 2i64 * (r2 as i64) - (r1 as i64);
-                let bc = 2i64 * (c2 as i64) - (c1 as i64);
-                if br >= 0 && br < height as i64 && bc >= 0 && bc < width as i64 {
-                    antinodes.insert((br as usize, bc as usize));
-                }
+    for ((r1, c1), (r2, c2)) in pairs(&antennas) {
+        for antinode in [(2 * r1 - r2, 2 * c1 - c2), (2 * r2 - r1, 2 * c2 - c1)] {
+            if bounds.contains(antinode) {
+                antinodes.insert(antinode);
             }
         }
     }
 
+    antinodes.len()
+}
+
+fn solve_part2(input: &str) -> usize {
+    let (bounds, antennas) = parse_input(input);
+    let mut antinodes = HashSet::new();
+
     //---------------------------------------------
-    // Part 2: Collinerar based antinodes
+    // Part 2: Collinear based antinodes
     //---------------------------------------------
     //
     // "An antinode occurs at any grid position exactly in line with
     //  at least two antennas of the same frequency, regardless of distance."
     //
-    // Approach:
-    // - For each pair of same-frequency antennas (A, B),
-    //   compute the reduced direction vector (dr, dc).
-    // - Start from A and move forward in steps of (dr, dc) until out of bounds,
-    //   marking each in-bounds grid position as an antinode.
-    // - Also move backward from A in steps of (-dr, -dc) until out of bounds.
-    // - We do i < j to avoid repeating the exact same pair in reverse,
-    //   but note we *do* run the line from each pair’s perspective.
-    //   This is fine, because duplicates go into a HashSet.
-    //
-    // This will hit all collinear integer points in the grid that lie on
-    // the line defined by (A, B).
-    //
-
-    // Convert grid size to i64 so we can compare with rr, cc safely.
-    let height_i64 = height as i64;
-    let width_i64 = width as i64;
+    // That's every lattice point on the line through A and B that lies in the grid,
+    // stepping by the gcd-reduced direction so none in between get skipped.
+    // Lines from different pairs overlap, duplicates go into a HashSet.
 
-    for coords in antennas.values() {
-        if coords.len() < 2 {
-            // Only one (or zero) antenna for this frequency => cannot form lines
+    for (a, b) in pairs(&antennas) {
+        // If two antennas share the same position, skip
+        if a == b {
             continue;
         }
+        antinodes.extend(geometry::lattice_line(a, b, &bounds));
+    }
 
-        for i in 0..coords.len() {
-            for j in i + 1..coords.len() {
-                let (r1, c1) = coords[i];
-                let (r2, c2) = coords[j];
-
-                // If two antennas share the same position, skip
-                if r1 == r2 && c1 == c2 {
-                    continue;
-                }
-
-                // Compute the reduced slope (dr, dc)
-                let mut dr = r2 as i64 - r1 as i64;
-                let mut dc = c2 as i64 - c1 as i64;
-                let g = gcd(dr, dc);
-                dr /= g;
-                dc /= g;
-
-                // -- Move forward from (r1,c1) in direction (dr, dc) --
-                let (mut rr, mut cc) = (r1 as i64, c1 as i64);
-                while rr >= 0 && rr < height_i64 && cc >= 0 && cc < width_i64 {
-                    antinodes.insert((rr as usize, cc as usize));
-                    rr += dr;
-                    cc += dc;
-                }
-
-                // -- Move backward from (r1,c1) in direction (-dr, -dc) --
-                let (mut rr, mut cc) = (r1 as i64, c1 as i64);
-                while rr >= 0 && rr < height_i64 && cc >= 0 && cc < width_i64 {
-                    antinodes.insert((rr as usize, cc as usize));
-                    rr -= dr;
-                    cc -= dc;
-                }
-            }
-        }
+    antinodes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 14);
+        assert_eq!(solve_part2(EXAMPLE), 34);
+    }
+
+    #[test]
+    fn empty_and_ragged_maps() {
+        // crash found by fuzzing, the width came from lines[0] which empty input doesnt have
+        assert_eq!(solve_part1(""), 0);
+        assert_eq!(solve_part2("a\n..\n...a\n"), 2);
     }
 
-    print!("{}", antinodes.len());
-}
\ No newline at end of file
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 252);
+        assert_eq!(solve_part2(&input), 839);
+    }
+}
@@ -0,0 +1,45 @@
+use common::rng::Rng;
+
+// disk map with `size` files, each 1-9 blocks long with 0-9 free blocks between them
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut map = String::with_capacity(size * 2);
+
+    for i in 0..size {
+        map.push((b'1' + rng.below(9) as u8) as char);
+        if i + 1 < size {
+            map.push((b'0' + rng.below(10) as u8) as char);
+        }
+    }
+
+    map.push('\n');
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Block, parse_disk_map, solve_part1, solve_part2};
+
+    #[test]
+    fn same_seed_same_disk() {
+        assert_eq!(generate(200, 9), generate(200, 9));
+        assert_ne!(generate(200, 9), generate(200, 10));
+    }
+
+    #[test]
+    fn generated_disk_parses_and_solves() {
+        let input = generate(500, 1);
+        assert_eq!(input.trim_end().len(), 2 * 500 - 1);
+        let last_file = parse_disk_map(&input)
+            .iter()
+            .rev()
+            .find_map(|block| match block {
+                Block::File(id) => Some(*id),
+                Block::Free => None,
+            });
+        assert_eq!(last_file, Some(499));
+        assert!(solve_part1(&input) > 0);
+        assert!(solve_part2(&input) > 0);
+    }
+}
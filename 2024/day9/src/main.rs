@@ -1,4 +1,4 @@
-use std::fs;
+mod generate;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Block {
@@ -6,19 +6,22 @@ enum Block {
     Free,
 }
 
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: common::alloc::Counting = common::alloc::Counting;
+
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
+    let input = common::input::load_or_generate("src/input.txt", generate::generate);
+    common::alloc::report("Part 1", || solve_part1(&input));
+    common::alloc::report("Part 2", || solve_part2(&input));
 }
 
 fn parse_disk_map(input: &str) -> Vec<Block> {
+    // anything that isnt a digit (stray whitespace etc) is skipped rather than a crash
     let digits: Vec<usize> = input
-        .trim()
         .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as usize)
         .collect();
 
     let mut blocks = Vec::new();
@@ -35,7 +38,7 @@ fn parse_disk_map(input: &str) -> Vec<Block> {
             Block::Free
         };
 
-        blocks.extend(std::iter::repeat(block).take(length));
+        blocks.extend(std::iter::repeat_n(block, length));
     }
 
     blocks
@@ -45,12 +48,8 @@ fn solve_part1(input: &str) -> usize {
     let mut disk = parse_disk_map(input);
 
     // compact by moving individual blocks
-    loop {
-        // find leftmost free space
-        let Some(free_idx) = disk.iter().position(|&b| b == Block::Free) else {
-            break;
-        };
-
+    // find leftmost free space
+    while let Some(free_idx) = disk.iter().position(|&b| b == Block::Free) {
         // find rightmost file block
         let Some(file_idx) = disk.iter().rposition(|&b| matches!(b, Block::File(_))) else {
             break;
@@ -149,4 +148,32 @@ fn checksum(disk: &[Block]) -> usize {
             }
         })
         .sum()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2333133121414131402\n";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 1928);
+        assert_eq!(solve_part2(EXAMPLE), 2858);
+    }
+
+    #[test]
+    fn ignores_non_digits() {
+        // crash found by fuzzing, to_digit(10).unwrap() on anything but a digit
+        assert_eq!(solve_part1("12 3-\r\n"), solve_part1("123"));
+        assert_eq!(solve_part2(""), 0);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE, "12345", "90909"], 5_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+}
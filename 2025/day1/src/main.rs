@@ -1,7 +1,6 @@
-use std::fs;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
@@ -12,25 +11,16 @@ fn solve_part1(input: &str) -> usize {
     let mut position = 50; // starting position
     let mut zero_count = 0;
 
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        // parse direction and distance
-        let direction = &line[0..1];
-        let distance: i32 = line[1..].parse().expect("Failed to parse distance");
-
+    for (direction, distance) in parse_rotations(input) {
         // Rotate the dial
         position = match direction {
             "L" => {
                 // left means subtract (toward lower numbers)
-                (position - distance).rem_euclid(100)
+                (position - distance % 100).rem_euclid(100)
             }
             "R" => {
                 // right means add (toward higher numbers)
-                (position + distance).rem_euclid(100)
+                (position + distance % 100).rem_euclid(100)
             }
             _ => panic!("Unknown direction: {}", direction),
         };
@@ -48,23 +38,14 @@ fn solve_part2(input: &str) -> usize {
     let mut position = 50; // starting position
     let mut zero_count = 0;
 
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        // parse direction and distance
-        let direction = &line[0..1];
-        let distance: i32 = line[1..].parse().expect("Failed to parse distance");
-
+    for (direction, distance) in parse_rotations(input) {
         // count how many times we pass through 0 during the rotation
         zero_count += count_zeros_in_rotation(position, direction, distance);
 
         // rotate the dial
         position = match direction {
-            "L" => (position - distance).rem_euclid(100),
-            "R" => (position + distance).rem_euclid(100),
+            "L" => (position - distance % 100).rem_euclid(100),
+            "R" => (position + distance % 100).rem_euclid(100),
             _ => panic!("Unknown direction: {}", direction),
         };
     }
@@ -72,11 +53,22 @@ fn solve_part2(input: &str) -> usize {
     zero_count
 }
 
+// (direction, distance) for every line that is an L or R and a distance, the rest are skipped
+// only whole laps matter past 100, the `% 100`s keep big distances from overflowing
+fn parse_rotations(input: &str) -> impl Iterator<Item = (&str, i32)> {
+    input.lines().filter_map(|line| {
+        let line = line.trim();
+        let direction = line.get(..1).filter(|&d| d == "L" || d == "R")?;
+        let distance: i32 = line[1..].parse().ok()?;
+        (distance >= 0).then_some((direction, distance))
+    })
+}
+
 fn count_zeros_in_rotation(start: i32, direction: &str, distance: i32) -> usize {
     // calculate the end position
     let end = match direction {
-        "L" => (start - distance).rem_euclid(100),
-        "R" => (start + distance).rem_euclid(100),
+        "L" => (start - distance % 100).rem_euclid(100),
+        "R" => (start + distance % 100).rem_euclid(100),
         _ => panic!("Unknown direction"),
     };
 
@@ -145,4 +137,61 @@ fn count_zeros_in_rotation(start: i32, direction: &str, distance: i32) -> usize
     }
 
     count
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    // turn the dial one click at a time and count every time it lands on 0
+    fn count_zeros_by_stepping(start: i32, direction: &str, distance: i32) -> usize {
+        let step = if direction == "L" { -1 } else { 1 };
+        let mut position = start;
+        let mut count = 0;
+        for _ in 0..distance {
+            position = (position + step).rem_euclid(100);
+            if position == 0 {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn skips_lines_that_arent_rotations() {
+        // crashes found by fuzzing, a multibyte first character split mid char,
+        // anything but a number panicked and a huge distance overflowed
+        let input = "L68\né5\nR\nX3\nL-5\nR2147483647\nL30\n";
+        assert_eq!(solve_part1(input), 0);
+        assert_eq!(solve_part2(input), 21474839);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        let example = "L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82\n";
+        common::fuzz::check(&[example], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+        assert_eq!((solve_part1(example), solve_part2(example)), (3, 6));
+    }
+
+    #[test]
+    fn rotation_matches_stepping() {
+        let mut rng = Rng::new(0x2025_0001);
+        for _ in 0..10_000 {
+            let start = rng.below(100) as i32;
+            let direction = if rng.below(2) == 0 { "L" } else { "R" };
+            let distance = rng.below(450) as i32;
+            assert_eq!(
+                count_zeros_in_rotation(start, direction, distance),
+                count_zeros_by_stepping(start, direction, distance),
+                "start={} {}{}",
+                start,
+                direction,
+                distance
+            );
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use std::fs;
+use std::ops::RangeInclusive;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
@@ -9,28 +9,33 @@ fn main() {
 }
 
 fn solve_part1(input: &str) -> u64 {
-    input
-        .trim()
-        .split(',')
-        .flat_map(parse_range)
-        .filter(|&id| is_repeated_exactly_twice(id))
-        .sum()
+    sum_invalid(common::par::threads(), input, is_repeated_exactly_twice)
 }
 
 fn solve_part2(input: &str) -> u64 {
+    sum_invalid(common::par::threads(), input, is_repeated_at_least_twice)
+}
+
+// the ranges are summed separately, so `threads` threads can take one each
+fn sum_invalid(threads: usize, input: &str, is_invalid: fn(u64) -> bool) -> u64 {
+    let ranges = parse_ranges(input);
+    common::par::map_with(threads, &ranges, |range| {
+        range.clone().filter(|&id| is_invalid(id)).sum::<u64>()
+    })
+    .into_iter()
+    .sum()
+}
+
+// ranges that dont read as start-end are skipped
+fn parse_ranges(input: &str) -> Vec<RangeInclusive<u64>> {
     input
         .trim()
         .split(',')
-        .flat_map(parse_range)
-        .filter(|&id| is_repeated_at_least_twice(id))
-        .sum()
-}
-
-fn parse_range(range: &str) -> impl Iterator<Item = u64> + '_ {
-    let parts: Vec<&str> = range.split('-').collect();
-    let start = parts[0].parse::<u64>().unwrap();
-    let end = parts[1].parse::<u64>().unwrap();
-    start..=end
+        .filter_map(|range| {
+            let (start, end) = range.split_once('-')?;
+            Some(start.parse().ok()?..=end.parse().ok()?)
+        })
+        .collect()
 }
 
 fn is_repeated_exactly_twice(n: u64) -> bool {
@@ -38,7 +43,7 @@ fn is_repeated_exactly_twice(n: u64) -> bool {
     let len = s.len();
 
     // must be even length to be splittable into two identical halves
-    if len % 2 != 0 {
+    if !len.is_multiple_of(2) {
         return false;
     }
 
@@ -63,7 +68,7 @@ fn is_repeated_at_least_twice(n: u64) -> bool {
     // the pattern must repeat at least twice, so max pattern length is len/2
     for pattern_len in 1..=len / 2 {
         // the total length must be evenly divisible by the pattern length
-        if len % pattern_len != 0 {
+        if !len.is_multiple_of(pattern_len) {
             continue;
         }
 
@@ -83,4 +88,49 @@ fn is_repeated_at_least_twice(n: u64) -> bool {
     }
 
     false
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,\
+1698522-1698528,446443-446449,38593856-38593862,565653-565659,\
+824824821-824824827,2121212118-2121212124";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 1227775554);
+        assert_eq!(solve_part2(EXAMPLE), 4174379265);
+    }
+
+    #[test]
+    fn any_thread_count_sums_the_same() {
+        for threads in [1, 2, 3, 8] {
+            assert_eq!(sum_invalid(threads, EXAMPLE, is_repeated_exactly_twice), 1227775554);
+            assert_eq!(sum_invalid(threads, EXAMPLE, is_repeated_at_least_twice), 4174379265);
+        }
+    }
+
+    #[test]
+    fn skips_ranges_that_dont_read() {
+        // crashes found by fuzzing, every range was unwrapped
+        assert_eq!(parse_ranges("11-22,,95,x-3,998-1012\n"), vec![11..=22, 998..=1012]);
+        assert_eq!(solve_part1("11-22,-,95-115"), 132);
+    }
+
+    #[test]
+    fn fuzz_parse() {
+        // only the parser, a mangled range can hold billions of ids to check
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            parse_ranges(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 30599400849);
+        assert_eq!(solve_part2(&input), 46270373595);
+    }
+}
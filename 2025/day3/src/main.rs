@@ -1,7 +1,6 @@
-use std::fs;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
@@ -16,29 +15,45 @@ fn solve_part2(input: &str) -> u64 {
     input.lines().map(|line| max_joltage_k(line, 12)).sum()
 }
 
+// only the digits of a bank count, stray characters are skipped instead of underflowing
+fn digits(bank: &str) -> Vec<u8> {
+    bank.bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| b - b'0')
+        .collect()
+}
+
 fn max_joltage_2(bank: &str) -> u32 {
-    let bytes = bank.as_bytes();
-    let n = bytes.len();
+    let digits = digits(bank);
+    let n = digits.len();
+
+    // a bank needs two batteries to turn on at all
+    if n < 2 {
+        return 0;
+    }
 
     // single pass right to left building suffix max on the fly
     // O(n)
-    let mut suffix_max = bytes[n - 1];
+    let mut suffix_max = digits[n - 1];
     let mut result = 0u32;
 
     for i in (0..n - 1).rev() {
-        let digit = (bytes[i] - b'0') as u32;
-        let max_after = (suffix_max - b'0') as u32;
-        result = result.max(digit * 10 + max_after);
-        suffix_max = suffix_max.max(bytes[i]);
+        result = result.max(digits[i] as u32 * 10 + suffix_max as u32);
+        suffix_max = suffix_max.max(digits[i]);
     }
 
     result
 }
 
 fn max_joltage_k(bank: &str, k: usize) -> u64 {
-    let digits: Vec<u8> = bank.bytes().map(|b| b - b'0').collect();
+    let digits = digits(bank);
     let n = digits.len();
 
+    // same for k, a bank with fewer than k batteries contributes nothing
+    if n < k {
+        return 0;
+    }
+
     // greedy selection with monotonic stack optimization
     // instead of searching for max in range each time, maintain invariant:
     // result contains largest k digits seen so far in lexicographic order
@@ -60,4 +75,80 @@ fn max_joltage_k(bank: &str, k: usize) -> u64 {
 
     // convert to u64 without intermediate allocations
     stack.iter().fold(0u64, |acc, &d| acc * 10 + d as u64)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    fn random_bank(rng: &mut Rng, len: usize) -> String {
+        (0..len)
+            .map(|_| (b'1' + rng.below(9) as u8) as char)
+            .collect()
+    }
+
+    // walk every subset of k digits (kept in order) and take the biggest
+    fn max_joltage_by_search(digits: &[u8], k: usize, acc: u64) -> u64 {
+        if k == 0 {
+            return acc;
+        }
+        (0..=digits.len() - k)
+            .map(|i| max_joltage_by_search(&digits[i + 1..], k - 1, acc * 10 + digits[i] as u64))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn greedy_matches_search() {
+        let mut rng = Rng::new(0x2025_0003);
+        for _ in 0..2_000 {
+            let n = 2 + rng.below(13) as usize;
+            let k = 1 + rng.below(n as u64) as usize;
+            let bank = random_bank(&mut rng, n);
+            let digits: Vec<u8> = bank.bytes().map(|b| b - b'0').collect();
+            assert_eq!(
+                max_joltage_k(&bank, k),
+                max_joltage_by_search(&digits, k, 0),
+                "bank={} k={}",
+                bank,
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn short_and_dirty_banks() {
+        // crashes found by fuzzing, n - k underflowed and so did non-digit bytes
+        assert_eq!(max_joltage_k("98765", 12), 0);
+        assert_eq!(max_joltage_2("7"), 0);
+        assert_eq!(max_joltage_2(""), 0);
+        assert_eq!(max_joltage_2("8 1\r9"), 89);
+        assert_eq!(solve_part2("987654321111111\n\n12\n"), 987654321111);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        let example = "987654321111111\n811111111111119\n234234234234278\n818181911112111\n";
+        common::fuzz::check(&[example], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn pair_matches_search() {
+        let mut rng = Rng::new(0x2025_0032);
+        for _ in 0..2_000 {
+            let n = 2 + rng.below(13) as usize;
+            let bank = random_bank(&mut rng, n);
+            let digits: Vec<u8> = bank.bytes().map(|b| b - b'0').collect();
+            assert_eq!(
+                max_joltage_2(&bank) as u64,
+                max_joltage_by_search(&digits, 2, 0),
+                "bank={}",
+                bank
+            );
+        }
+    }
+}
@@ -1,95 +1,97 @@
-use std::fs;
+use common::automaton::{Automaton, Generation, Neighbourhood};
+use common::bitgrid::BitGrid;
+use common::cells::CellMap;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
-}
-
-fn solve_part1(input: &str) -> usize {
-    let grid: Vec<&[u8]> = input.lines().map(|line| line.as_bytes()).collect();
 
-    let mut accessible = 0;
-
-    for r in 0..grid.len() {
-        for c in 0..grid[r].len() {
-            if grid[r][c] == b'@' && count_neighbors(&grid, r, c) < 4 {
-                accessible += 1;
-            }
+    // `--generations` shows how the removal went, round by round
+    if std::env::args().any(|arg| arg == "--generations") {
+        for generation in remove_rolls(&input) {
+            println!("{}", generation);
         }
     }
+}
 
-    accessible
+fn solve_part1(input: &str) -> usize {
+    let rolls = BitGrid::parse(input, '@');
+    accessible(&rolls).count_ones()
 }
 
 fn solve_part2(input: &str) -> usize {
-    // need mutable grid for part 2
-    let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
-
-    let mut total_removed = 0;
-
-    // repeatedly remove accessible rolls until none left
-    loop {
-        // find all accessible rolls this round
-        let mut to_remove = Vec::new();
-
-        for r in 0..grid.len() {
-            for c in 0..grid[r].len() {
-                if grid[r][c] == b'@' && count_neighbors_mut(&grid, r, c) < 4 {
-                    to_remove.push((r, c));
-                }
-            }
-        }
-
-        if to_remove.is_empty() {
-            break;
-        }
+    remove_rolls(input).iter().map(|g| g.changed).sum()
+}
 
-        // remove them all
-        for (r, c) in to_remove.iter() {
-            grid[*r][*c] = b'.';
-        }
+// repeatedly remove accessible rolls until none left
+// removing a roll only changes what its neighbours see, so after the first pass
+// only the cells next to a removed roll get checked again
+fn remove_rolls(input: &str) -> Vec<Generation> {
+    let rolls = CellMap::parse(input, |ch| ch == '@');
+    Automaton::new(rolls, Neighbourhood::moore(1), forklift).settle()
+}
 
-        total_removed += to_remove.len();
-    }
+// a roll stays unless fewer than 4 of the 8 cells around it hold rolls
+fn forklift(roll: bool, around: &[bool]) -> bool {
+    roll && around.iter().filter(|&&n| n).count() >= 4
+}
 
-    total_removed
+// rolls with fewer than 4 rolls around them, the whole grid 64 cells at a time
+fn accessible(rolls: &BitGrid) -> BitGrid {
+    rolls & &rolls.fewer_neighbours_than(4)
 }
 
-fn count_neighbors(grid: &[&[u8]], r: usize, c: usize) -> usize {
-    const DIRS: [(isize, isize); 8] = [
-        (-1, -1), (-1, 0), (-1, 1),
-        (0, -1),           (0, 1),
-        (1, -1),  (1, 0),  (1, 1),
-    ];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
 
-    DIRS.iter()
-        .filter(|&&(dr, dc)| {
-            let nr = r.wrapping_add_signed(dr);
-            let nc = c.wrapping_add_signed(dc);
-            grid.get(nr)
-                .and_then(|row| row.get(nc))
-                .map_or(false, |&cell| cell == b'@')
-        })
-        .count()
-}
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 1523);
+        assert_eq!(solve_part2(&input), 9290);
+    }
 
-fn count_neighbors_mut(grid: &[Vec<u8>], r: usize, c: usize) -> usize {
-    const DIRS: [(isize, isize); 8] = [
-        (-1, -1), (-1, 0), (-1, 1),
-        (0, -1),           (0, 1),
-        (1, -1),  (1, 0),  (1, 1),
-    ];
+    #[test]
+    fn fuzz_solve() {
+        let example = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n";
+        common::fuzz::check(&[example], 5_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
 
-    DIRS.iter()
-        .filter(|&&(dr, dc)| {
-            let nr = r.wrapping_add_signed(dr);
-            let nc = c.wrapping_add_signed(dc);
-            grid.get(nr)
-                .and_then(|row| row.get(nc))
-                .map_or(false, |&cell| cell == b'@')
-        })
-        .count()
+    // the rounds the puzzle describes, everything accessible goes at once, should end
+    // up the same as the queued removal, and the first round is part 1
+    #[test]
+    fn queued_removal_matches_rounds() {
+        let mut rng = Rng::new(0x2025_0004);
+        for _ in 0..10 {
+            let input: String = (0..30)
+                .map(|_| {
+                    let row: String = (0..40)
+                        .map(|_| if rng.chance(65) { '@' } else { '.' })
+                        .collect();
+                    row + "\n"
+                })
+                .collect();
+
+            let rolls = CellMap::parse(&input, |ch| ch == '@');
+            let mut rounds = Automaton::new(rolls.clone(), Neighbourhood::moore(1), forklift);
+            let mut queued = Automaton::new(rolls, Neighbourhood::moore(1), forklift);
+            let by_round = rounds.run(usize::MAX);
+            let settled = queued.settle();
+
+            assert_eq!(rounds.cells(), queued.cells());
+            assert_eq!(by_round[0].changed, solve_part1(&input));
+            assert_eq!(
+                by_round.iter().map(|g| g.changed).sum::<usize>(),
+                settled.iter().map(|g| g.changed).sum::<usize>()
+            );
+        }
+    }
 }
@@ -1,7 +1,6 @@
-use std::fs;
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
+    let input = common::input::load("src/input.txt");
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("Part 1: {}", part1);
@@ -51,13 +50,55 @@ fn parse_input(input: &str) -> (Vec<(u64, u64)>, Vec<u64>) {
         if line.is_empty() {
             break;
         }
+        // skip ranges that dont read, or run backwards and would count negative ids
         let mut parts = line.split("-");
-        let start = parts.next().unwrap().parse().unwrap();
-        let end = parts.next().unwrap().parse().unwrap();
-        ranges.push((start, end));
+        let start = parts.next().and_then(|s| s.parse().ok());
+        let end = parts.next().and_then(|s| s.parse().ok());
+        if let (Some(start), Some(end)) = (start, end)
+            && start <= end
+        {
+            ranges.push((start, end));
+        }
     }
 
-    let ingredients = lines.map(|line| line.parse().unwrap()).collect();
+    let ingredients = lines.filter_map(|line| line.parse().ok()).collect();
 
     (ranges, ingredients)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32\n";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 3);
+        assert_eq!(solve_part2(EXAMPLE), 14);
+    }
+
+    #[test]
+    fn skips_lines_that_dont_read() {
+        // crashes found by fuzzing, every line was unwrapped and a backwards
+        // range underflowed its length
+        let input = "3-5\n9-7\n10\nx-2\n\n4\nfour\n8\n";
+        assert_eq!(solve_part1(input), 1);
+        assert_eq!(solve_part2(input), 3);
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 525);
+        assert_eq!(solve_part2(&input), 333892124923577);
+    }
+}
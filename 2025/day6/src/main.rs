@@ -1,35 +1,41 @@
-use std::fs;
+use common::num::{self, Signed};
 
 fn main() {
-    let input = fs::read_to_string("src/input.txt").expect("Failed to read input");
-    let part1 = solve_part1(&input);
-    let part2 = solve_part2(&input);
+    let input = common::input::load("src/input.txt");
+    let part1 = num::checked_or_exit(|| solve_part1(&input));
+    let part2 = num::checked_or_exit(|| solve_part2(&input));
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 }
 
-fn solve_part1(input: &str) -> i64 {
-    let lines: Vec<&str> = input.lines().collect();
+// the worksheet as a grid of chars with every row padded to the same width,
+// so columns line up even when lines are ragged or hold multi byte chars
+struct Worksheet {
+    rows: Vec<Vec<char>>,
+    // (start, end) column ranges of each problem
+    problems: Vec<(usize, usize)>,
+}
+
+fn parse_worksheet(input: &str) -> Worksheet {
+    let mut rows: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
     // find max width
-    let max_width = lines.iter().map(|line| line.len()).max().unwrap();
+    let max_width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in &mut rows {
+        row.resize(max_width, ' ');
+    }
 
     // identify columns that are all spaces (separators)
-    let mut is_separator = vec![true; max_width];
-    for line in &lines {
-        for (i, ch) in line.chars().enumerate() {
-            if ch != ' ' {
-                is_separator[i] = false;
-            }
-        }
-    }
+    let is_separator: Vec<bool> = (0..max_width)
+        .map(|col| rows.iter().all(|row| row[col] == ' '))
+        .collect();
 
     // find problem boundaries
     let mut problems = Vec::new();
     let mut start = None;
 
-    for i in 0..max_width {
-        if is_separator[i] {
+    for (i, &separator) in is_separator.iter().enumerate() {
+        if separator {
             if let Some(s) = start {
                 problems.push((s, i));
                 start = None;
@@ -44,124 +50,133 @@ fn solve_part1(input: &str) -> i64 {
         problems.push((s, max_width));
     }
 
-    let mut total = 0i64;
-
-    // solve each problem
-    for (start_col, end_col) in problems {
-        let mut nums = Vec::new();
-        let mut op = '+';
-
-        for (row_idx, line) in lines.iter().enumerate() {
-            let segment: String = line.chars()
-                .skip(start_col)
-                .take(end_col - start_col)
-                .collect();
-            let trimmed = segment.trim();
-
-            if row_idx < lines.len() - 1 {
-                // all rows except last are numbers
-                if let Ok(num) = trimmed.parse::<i64>() {
-                    nums.push(num);
-                }
-            } else {
-                // last row is operator
-                if trimmed == "*" {
-                    op = '*';
-                }
-            }
-        }
+    Worksheet { rows, problems }
+}
 
-        let result: i64 = if op == '*' {
-            nums.iter().copied().product()
-        } else {
-            nums.iter().copied().sum()
-        };
+// products of a handful of long numbers are what can overflow, so they go through common::num
+fn apply(op: char, nums: &[Signed]) -> Signed {
+    if op == '*' {
+        num::product(nums.iter().copied())
+    } else {
+        num::sum(nums.iter().copied())
+    }
+}
 
-        total += result;
+// last row is the operator row, a '*' anywhere under the problem makes it a product
+fn operator(sheet: &Worksheet, start_col: usize, end_col: usize) -> char {
+    match sheet.rows.last() {
+        Some(row) if row[start_col..end_col].contains(&'*') => '*',
+        _ => '+',
     }
+}
 
-    total
+fn solve_part1(input: &str) -> Signed {
+    num::sum(
+        read_by_rows(&parse_worksheet(input))
+            .iter()
+            .map(|(op, nums)| apply(*op, nums)),
+    )
 }
 
-fn solve_part2(input: &str) -> i64 {
-    let lines: Vec<&str> = input.lines().collect();
+fn solve_part2(input: &str) -> Signed {
+    num::sum(
+        read_by_columns(&parse_worksheet(input))
+            .iter()
+            .map(|(op, nums)| apply(*op, nums)),
+    )
+}
 
-    // find max width
-    let max_width = lines.iter().map(|line| line.len()).max().unwrap();
+// part 1 reading, every row above the operator row is one number
+fn read_by_rows(sheet: &Worksheet) -> Vec<(char, Vec<Signed>)> {
+    let num_rows = sheet.rows.len().saturating_sub(1);
+
+    sheet
+        .problems
+        .iter()
+        .map(|&(start_col, end_col)| {
+            let nums = sheet.rows[..num_rows]
+                .iter()
+                .filter_map(|row| {
+                    let segment: String = row[start_col..end_col].iter().collect();
+                    segment.trim().parse().ok()
+                })
+                .collect();
+            (operator(sheet, start_col, end_col), nums)
+        })
+        .collect()
+}
 
-    // identify columns that are all spaces (separators)
-    let mut is_separator = vec![true; max_width];
-    for line in &lines {
-        for (i, ch) in line.chars().enumerate() {
-            if ch != ' ' {
-                is_separator[i] = false;
-            }
-        }
-    }
+// part 2 reading, each column is one number read top to bottom
+fn read_by_columns(sheet: &Worksheet) -> Vec<(char, Vec<Signed>)> {
+    let num_rows = sheet.rows.len().saturating_sub(1);
+
+    sheet
+        .problems
+        .iter()
+        .map(|&(start_col, end_col)| {
+            let nums = (start_col..end_col)
+                .filter_map(|col| {
+                    let num_str: String = sheet.rows[..num_rows]
+                        .iter()
+                        .map(|row| row[col])
+                        .filter(|&ch| ch != ' ')
+                        .collect();
+                    num_str.parse().ok()
+                })
+                .collect();
+            (operator(sheet, start_col, end_col), nums)
+        })
+        .collect()
+}
 
-    // find problem boundaries
-    let mut problems = Vec::new();
-    let mut start = None;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for i in 0..max_width {
-        if is_separator[i] {
-            if let Some(s) = start {
-                problems.push((s, i));
-                start = None;
-            }
-        } else if start.is_none() {
-            start = Some(i);
-        }
-    }
+    const EXAMPLE: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  \n";
 
-    // add last problem if exists
-    if let Some(s) = start {
-        problems.push((s, max_width));
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 4277556);
+        assert_eq!(solve_part2(EXAMPLE), 3263827);
     }
 
-    let mut total = 0i64;
-
-    // solve each problem
-    for (start_col, end_col) in problems {
-        let num_rows = lines.len() - 1;
-        let mut nums_by_col = Vec::new();
-        let mut op = '+';
-
-        // For each column in this problem, build a number by reading top to bottom
-        for col in start_col..end_col {
-            let mut num_str = String::new();
-            for row_idx in 0..num_rows {
-                let ch = lines[row_idx].chars().nth(col).unwrap_or(' ');
-                if ch != ' ' {
-                    num_str.push(ch);
-                }
-            }
-            if !num_str.is_empty() {
-                nums_by_col.push(num_str);
-            }
-        }
-
-        // Check operator row
-        for col in start_col..end_col {
-            let ch = lines[num_rows].chars().nth(col).unwrap_or(' ');
-            if ch == '*' {
-                op = '*';
-            }
-        }
+    #[test]
+    fn ragged_and_empty_sheets() {
+        // crashes found by fuzzing, an empty sheet unwrapped the max width
+        assert_eq!(solve_part1(""), 0);
+        assert_eq!(solve_part2(""), 0);
+        // a multi byte char used to shift chars().skip columns against the byte widths
+        assert_eq!(solve_part1("12 é\n3  4\n*  +\n"), 36 + 4);
+        assert_eq!(solve_part1("12\n3\n*\n"), 36);
+    }
 
-        let nums: Vec<i64> = nums_by_col
-            .iter()
-            .filter_map(|s| s.parse().ok())
-            .collect();
+    // the overflow fuzzing turned up, no separator column so part 2 multiplies all 12 columns
+    #[cfg(any(feature = "checked", feature = "wide"))]
+    const OVERFLOWS: &str = "123 328 1 64\n4564 38723\n6215314\n*";
 
-        let result: i64 = if op == '*' {
-            nums.iter().copied().product()
-        } else {
-            nums.iter().copied().sum()
-        };
+    #[cfg(feature = "checked")]
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn checked_reports_overflow() {
+        solve_part2(OVERFLOWS);
+    }
 
-        total += result;
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide_gets_past_64_bits() {
+        // 146 * 252 * 361 * 45 * 33 * 231 * 884 * 7 * 12 * 3 * 6 * 4
+        assert_eq!(solve_part2(OVERFLOWS), 24_359_203_272_305_341_440);
     }
 
-    total
+    // only the reading is fuzzed, a few extra digits are enough to overflow the
+    // products and that is down to the arithmetic rather than the parser
+    #[test]
+    fn fuzz_parse() {
+        common::fuzz::check(&[EXAMPLE], 20_000, |input| {
+            let sheet = parse_worksheet(input);
+            read_by_rows(&sheet);
+            read_by_columns(&sheet);
+        });
+    }
 }
@@ -1,17 +1,137 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
+
+use common::cells::CellMap;
+use common::num::{self, Arith, Count};
+use common::sim::{Colour, Frame, Simulation};
 
 fn main() {
-    let input = std::fs::read_to_string("src/input.txt").unwrap();
+    let input = common::input::load("src/input.txt");
     println!("Part 1: {}", solve_part1(&input));
-    println!("Part 2: {}", solve_part2(&input));
+    println!("Part 2: {}", num::checked_or_exit(|| solve_part2(&input)));
+
+    if common::sim::requested() {
+        common::sim::play(&mut Beams::new(&input));
+    }
+}
+
+// the beams moving down one row per step, counting timelines per column as they go
+struct Beams {
+    grid: Vec<Vec<char>>,
+    row: usize,
+    timelines: Vec<Count>,
+    // every cell a beam has been through, only `--play` draws it but it's one flag per
+    // cell so part 2 can keep paying for it
+    lit: CellMap<bool>,
+    splits: usize,
+}
+
+impl Beams {
+    fn new(input: &str) -> Self {
+        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let top = grid.first().map_or(&[][..], |row| &row[..]);
+        let start_col = top.iter().position(|&ch| ch == 'S').unwrap_or(0);
+
+        let mut timelines = vec![0; top.len()];
+        if let Some(start) = timelines.get_mut(start_col) {
+            *start = 1;
+        }
+
+        Self {
+            lit: CellMap::new(grid.len(), top.len(), false),
+            grid,
+            row: 0,
+            timelines,
+            splits: 0,
+        }
+    }
+
+    // timelines still going on the current row, all of them once the beams reach the bottom
+    fn timelines(&self) -> Count {
+        num::sum(self.timelines.iter().copied())
+    }
+}
+
+impl Simulation for Beams {
+    fn step(&mut self) -> bool {
+        let next_row = self.row + 1;
+        if next_row >= self.grid.len() {
+            return false;
+        }
+
+        let width = self.timelines.len();
+        let mut next = vec![0; width];
+        for (col, &count) in self.timelines.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            if cell(&self.grid, next_row, col) == '^' {
+                self.splits += 1;
+                if col > 0 {
+                    next[col - 1] = next[col - 1].plus(count);
+                }
+                if col + 1 < width {
+                    next[col + 1] = next[col + 1].plus(count);
+                }
+            } else {
+                next[col] = next[col].plus(count);
+            }
+        }
+
+        for (col, &count) in next.iter().enumerate() {
+            if count > 0 {
+                self.lit.insert((next_row, col));
+            }
+        }
+        self.timelines = next;
+        self.row = next_row;
+        true
+    }
+
+    fn render(&self) -> Frame {
+        let text: String = self
+            .grid
+            .iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect();
+        let mut frame = Frame::from_chars(&text);
+        for ((row, col), _) in self.lit.iter().filter(|(_, lit)| **lit) {
+            frame.set(row, col, '|', Some(Colour::Cyan));
+        }
+        // splitters a beam just hit, none yet on the top row
+        for col in 0..self.timelines.len() {
+            if self.row > 0
+                && cell(&self.grid, self.row, col) == '^'
+                && self.lit.contains((self.row - 1, col))
+            {
+                frame.paint(self.row, col, Colour::Yellow);
+            }
+        }
+        frame.set_status(format!(
+            "row {}  splits {}  timelines {}",
+            self.row,
+            self.splits,
+            self.timelines()
+        ));
+        frame
+    }
+}
+
+// rows can be ragged, anything past the end of one is empty space
+fn cell(grid: &[Vec<char>], row: usize, col: usize) -> char {
+    grid[row].get(col).copied().unwrap_or('.')
 }
 
 fn solve_part1(input: &str) -> usize {
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    let width = grid.first().map_or(0, |row| row.len());
+    if width == 0 {
+        // no top row for the beam to start on
+        return 0;
+    }
 
     // find the starting position (S)
     let mut start_col = 0;
-    for (col, &ch) in grid[0].iter().enumerate() {
+    for (col, &ch) in grid.iter().take(1).flatten().enumerate() {
         if ch == 'S' {
             start_col = col;
             break;
@@ -38,9 +158,7 @@ fn solve_part1(input: &str) -> usize {
         }
 
         let next_row = row + 1;
-        let cell = grid[next_row][col];
-
-        if cell == '^' {
+        if cell(&grid, next_row, col) == '^' {
             // hit a splitter count it and spawn two new beams
             split_count += 1;
 
@@ -50,7 +168,7 @@ fn solve_part1(input: &str) -> usize {
             }
 
             // spawn right beam (if not at right edge)
-            if col < grid[0].len() - 1 {
+            if col + 1 < width {
                 queue.push_back((next_row, col + 1));
             }
         } else {
@@ -62,78 +180,74 @@ fn solve_part1(input: &str) -> usize {
     split_count
 }
 
-fn solve_part2(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+fn solve_part2(input: &str) -> Count {
+    // the same row by row sweep `--play` shows, run to the bottom
+    let mut beams = Beams::new(input);
+    common::sim::run(&mut beams, usize::MAX);
+    beams.timelines()
+}
 
-    // find the starting position (S)
-    let mut start_col = 0;
-    for (col, &ch) in grid[0].iter().enumerate() {
-        if ch == 'S' {
-            start_col = col;
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve_part1(EXAMPLE), 21);
+        assert_eq!(solve_part2(EXAMPLE), 40);
     }
 
-    // count the number of paths to reach each position
-    // paths[(row, col)] = number of distinct timelines that reach this position
-    let mut paths: HashMap<(usize, usize), usize> = HashMap::new();
-    paths.insert((0, start_col), 1);
-
-    let mut queue = VecDeque::new();
-    queue.push_back((0, start_col));
-
-    while let Some((row, col)) = queue.pop_front() {
-        let current_paths = *paths.get(&(row, col)).unwrap_or(&0);
-
-        if current_paths == 0 {
-            continue;
-        }
-
-        // move down one row
-        if row + 1 >= grid.len() {
-            continue; // exit the manifold
-        }
-
-        let next_row = row + 1;
-        let cell = grid[next_row][col];
-
-        if cell == '^' {
-            // hit a splitter paths split into left and right
+    #[test]
+    fn empty_and_ragged_manifolds() {
+        // crashes found by fuzzing, both parts read grid[0] and indexed
+        // every row as wide as the first
+        assert_eq!((solve_part1(""), solve_part2("")), (0, 0));
+        assert_eq!((solve_part1("\n^"), solve_part2("\n^")), (0, 0));
+        let input = "..S..\n.\n..^..\n";
+        assert_eq!((solve_part1(input), solve_part2(input)), (1, 2));
+    }
 
-            // left path
-            if col > 0 {
-                let left_pos = (next_row, col - 1);
-                let prev_count = *paths.get(&left_pos).unwrap_or(&0);
-                if prev_count == 0 {
-                    queue.push_back(left_pos);
-                }
-                paths.insert(left_pos, prev_count + current_paths);
-            }
+    #[test]
+    fn splitter_on_the_top_row() {
+        // the first frame looked above row 0 for a beam hitting the splitter
+        let mut beams = Beams::new("S^.\n...\n");
+        beams.render();
+        assert!(beams.step());
+        beams.render();
+        assert_eq!(beams.timelines(), 1);
+    }
 
-            // right path
-            if col < grid[0].len() - 1 {
-                let right_pos = (next_row, col + 1);
-                let prev_count = *paths.get(&right_pos).unwrap_or(&0);
-                if prev_count == 0 {
-                    queue.push_back(right_pos);
-                }
-                paths.insert(right_pos, prev_count + current_paths);
-            }
-        } else {
-            // empty space continue down
-            let next_pos = (next_row, col);
-            let prev_count = *paths.get(&next_pos).unwrap_or(&0);
-            if prev_count == 0 {
-                queue.push_back(next_pos);
-            }
-            paths.insert(next_pos, prev_count + current_paths);
-        }
+    #[test]
+    fn fuzz_solve() {
+        common::fuzz::check(&[EXAMPLE], 5_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
     }
 
-    // sum all paths that reach the bottom row or exit the grid
-    let bottom_row = grid.len() - 1;
-    paths.iter()
-        .filter(|((row, _), _)| *row == bottom_row)
-        .map(|(_, count)| count)
-        .sum()
-}
\ No newline at end of file
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 1642);
+        assert_eq!(solve_part2(&input), 47274292756692);
+    }
+}
@@ -0,0 +1,36 @@
+use common::rng::Rng;
+
+// `size` junction boxes spread over the same 0..100000 cube as the real input
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut out = String::new();
+
+    for _ in 0..size {
+        let x = rng.range(0, 99_999);
+        let y = rng.range(0, 99_999);
+        let z = rng.range(0, 99_999);
+        out.push_str(&format!("{},{},{}\n", x, y, z));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_points, solve_part1, solve_part2};
+
+    #[test]
+    fn same_seed_same_boxes() {
+        assert_eq!(generate(100, 8), generate(100, 8));
+        assert_ne!(generate(100, 8), generate(100, 9));
+    }
+
+    #[test]
+    fn generated_boxes_parse_and_solve() {
+        let input = generate(1500, 1);
+        assert_eq!(parse_points(&input).len(), 1500);
+        assert!(solve_part1(&input) > 0);
+        assert!(solve_part2(&input) > 0);
+    }
+}
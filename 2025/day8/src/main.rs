@@ -1,9 +1,17 @@
 use std::collections::HashMap;
 
+use common::spatial::{KdTree, Metric};
+
+mod generate;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: common::alloc::Counting = common::alloc::Counting;
+
 fn main() {
-    let input = std::fs::read_to_string("src/input.txt").unwrap();
-    println!("Part 1: {}", solve_part1(&input));
-    println!("Part 2: {}", solve_part2(&input));
+    let input = common::input::load_or_generate("src/input.txt", generate::generate);
+    common::alloc::report("Part 1", || solve_part1(&input));
+    common::alloc::report("Part 2", || solve_part2(&input));
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,11 +22,8 @@ struct Point3D {
 }
 
 impl Point3D {
-    fn distance_squared(&self, other: &Point3D) -> i64 {
-        let dx = (self.x - other.x) as i64;
-        let dy = (self.y - other.y) as i64;
-        let dz = (self.z - other.z) as i64;
-        dx * dx + dy * dy + dz * dz
+    fn coords(&self) -> [i64; 3] {
+        [self.x as i64, self.y as i64, self.z as i64]
     }
 }
 
@@ -73,35 +78,81 @@ impl UnionFind {
     }
 }
 
-fn solve_part1(input: &str) -> usize {
-    // parse all junction box positions
-    let points: Vec<Point3D> = input
+// lines that arent three numbers are skipped
+fn parse_points(input: &str) -> Vec<Point3D> {
+    input
         .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| {
-            let parts: Vec<i32> = line.split(',').map(|s| s.trim().parse().unwrap()).collect();
-            Point3D {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
-            }
+        .filter_map(|line| {
+            let parts: Vec<i32> = line
+                .split(',')
+                .map(|s| s.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            let &[x, y, z] = &parts[..] else {
+                return None;
+            };
+            Some(Point3D { x, y, z })
         })
-        .collect();
+        .collect()
+}
 
-    let n = points.len();
-    println!("Total junction boxes: {}", n);
+// pairs as (distance squared, i, j) with i < j, shortest first, same order as sorting
+// every pair, but only every point's k nearest neighbours are paired up
+// also returns the cutoff: any pair left out is at least that far apart, so the
+// list is exact up to there (i64::MAX when every pair is in it)
+fn nearby_pairs(points: &[Point3D], tree: &KdTree<3>, k: usize) -> (Vec<(i64, usize, usize)>, i64) {
+    let mut pairs = Vec::new();
+    let mut cutoff = i64::MAX;
 
-    // generate all pairs with their distances
-    let mut edges: Vec<(i64, usize, usize)> = Vec::new();
-    for i in 0..n {
-        for j in i + 1..n {
-            let dist_sq = points[i].distance_squared(&points[j]);
-            edges.push((dist_sq, i, j));
+    for (i, p) in points.iter().enumerate() {
+        // the point itself comes back first, at distance 0
+        let nearest = tree.nearest(&p.coords(), k + 1, Metric::Euclidean);
+        if nearest.len() == k + 1 {
+            cutoff = cutoff.min(nearest[k].0);
+        }
+        for &(dist_sq, j) in &nearest {
+            if i != j {
+                pairs.push((dist_sq, i.min(j), i.max(j)));
+            }
         }
     }
 
-    // sort edges by distance
-    edges.sort_unstable();
+    pairs.sort_unstable();
+    pairs.dedup();
+    (pairs, cutoff)
+}
+
+// starts with a few neighbours per point and doubles until `exact` accepts the pairs
+fn with_nearby_pairs<T>(
+    points: &[Point3D],
+    exact: impl Fn(&[(i64, usize, usize)], i64) -> Option<T>,
+) -> T {
+    let tree = KdTree::new(points.iter().map(Point3D::coords));
+    let mut k = 16;
+    loop {
+        let (pairs, cutoff) = nearby_pairs(points, &tree, k);
+        if let Some(result) = exact(&pairs, cutoff) {
+            return result;
+        }
+        k *= 2;
+    }
+}
+
+fn solve_part1(input: &str) -> usize {
+    // parse all junction box positions
+    let points = parse_points(input);
+
+    let n = points.len();
+    println!("Total junction boxes: {}", n);
+
+    // the 1000 closest pairs, the old way of sorting every pair took O(n^2) memory
+    let edges = with_nearby_pairs(&points, |pairs, cutoff| {
+        let closest = &pairs[..pairs.len().min(1000)];
+        let exact = match closest.get(999) {
+            Some(&(dist_sq, _, _)) => dist_sq < cutoff,
+            None => cutoff == i64::MAX,
+        };
+        exact.then(|| closest.to_vec())
+    });
 
     // use union find to track connected components
     let mut uf = UnionFind::new(n);
@@ -110,9 +161,6 @@ fn solve_part1(input: &str) -> usize {
     let mut connections_attempted = 0;
     let mut successful_unions = 0;
     for (_, i, j) in edges {
-        if connections_attempted >= 1000 {
-            break;
-        }
         connections_attempted += 1;
 
         if uf.union(i, j) {
@@ -138,53 +186,118 @@ fn solve_part1(input: &str) -> usize {
     }
 }
 
-fn solve_part2(input: &str) -> i32 {
+fn solve_part2(input: &str) -> i64 {
     // parse all junction box positions
-    let points: Vec<Point3D> = input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| {
-            let parts: Vec<i32> = line.split(',').map(|s| s.trim().parse().unwrap()).collect();
-            Point3D {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
-            }
-        })
-        .collect();
+    let points = parse_points(input);
+    if points.is_empty() {
+        // nothing to connect, so no last connection either
+        return 0;
+    }
 
     let n = points.len();
 
-    // generate all pairs with their distances
-    let mut edges: Vec<(i64, usize, usize)> = Vec::new();
-    for i in 0..n {
-        for j in i + 1..n {
-            let dist_sq = points[i].distance_squared(&points[j]);
-            edges.push((dist_sq, i, j));
+    // keep connecting until we have only 1 component
+    // thats exact as long as the last connection is shorter than the cutoff,
+    // every pair before it is then in the list
+    let last_connection = with_nearby_pairs(&points, |pairs, cutoff| {
+        let mut uf = UnionFind::new(n);
+        let mut components = n;
+        for &(dist_sq, i, j) in pairs {
+            if uf.union(i, j) {
+                components -= 1;
+                if components == 1 {
+                    return (dist_sq < cutoff).then_some((i, j));
+                }
+            }
         }
-    }
+        // not connected yet, needs more neighbours
+        (cutoff == i64::MAX).then_some((0, 0))
+    });
 
-    // sort edges by distance
-    edges.sort_unstable();
+    // multiply the X coordinates of the last two junction boxes connected
+    // 64 bit since two coordinates near 100000 overflow an i32
+    let (i, j) = last_connection;
+    points[i].x as i64 * points[j].x as i64
+}
 
-    // use union find to track connected components
-    let mut uf = UnionFind::new(n);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // keep connecting until we have only 1 component
-    let mut last_connection = (0, 0);
-    for (_, i, j) in edges {
-        if uf.union(i, j) {
-            last_connection = (i, j);
+    // the old way, every pair sorted, as (part 1, part 2)
+    fn all_pairs(input: &str) -> (usize, i64) {
+        let points = parse_points(input);
+        let n = points.len();
+        let mut edges = Vec::new();
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = (points[i].coords(), points[j].coords());
+                edges.push((Metric::Euclidean.distance(&a, &b), i, j));
+            }
+        }
+        edges.sort_unstable();
 
-            // check if we have only 1 component left
-            let sizes = uf.get_component_sizes();
-            if sizes.len() == 1 {
-                break;
+        let mut uf = UnionFind::new(n);
+        for &(_, i, j) in &edges[..edges.len().min(1000)] {
+            uf.union(i, j);
+        }
+        let mut sizes = uf.get_component_sizes();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        let part1 = sizes.iter().take(3).product();
+
+        let mut uf = UnionFind::new(n);
+        let mut components = n;
+        for &(_, i, j) in &edges {
+            if uf.union(i, j) {
+                components -= 1;
+                if components == 1 {
+                    return (part1, points[i].x as i64 * points[j].x as i64);
+                }
             }
         }
+        (part1, 0)
     }
 
-    // multiply the X coordinates of the last two junction boxes connected
-    let (i, j) = last_connection;
-    points[i].x * points[j].x
+    #[test]
+    fn nearest_neighbour_pairs_match_all_pairs() {
+        // under 1000 pairs in total, a clumped layout, and the generator's big cube
+        let mut inputs = vec!["1,2,3\n4,5,6\n".to_string()];
+        inputs.push((0..40).map(|i| format!("{},{},{}\n", i % 3, i / 3, 7)).collect());
+        inputs.extend((0..4).map(|seed| generate::generate(600, seed)));
+
+        for input in &inputs {
+            assert_eq!(
+                (solve_part1(input), solve_part2(input)),
+                all_pairs(input),
+                "{:?}",
+                input.lines().take(3).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn skips_lines_that_dont_read() {
+        // crashes found by fuzzing, every coordinate was unwrapped and
+        // with no boxes part 2 read the first one anyway
+        let points = parse_points("1,2,3\n4,5\nx,1,2\n6,7,8,9\n\n");
+        assert_eq!(points, vec![Point3D { x: 1, y: 2, z: 3 }]);
+        // part 1 is the empty product
+        assert_eq!((solve_part1(""), solve_part2("")), (1, 0));
+    }
+
+    #[test]
+    fn fuzz_solve() {
+        let example = "162,817,812\n57,618,57\n906,360,560\n592,479,940\n352,342,300\n";
+        common::fuzz::check(&[example], 5_000, |input| {
+            solve_part1(input);
+            solve_part2(input);
+        });
+    }
+
+    #[test]
+    fn real_input() {
+        let input = common::input::load("src/input.txt");
+        assert_eq!(solve_part1(&input), 68112);
+        assert_eq!(solve_part2(&input), 44543856);
+    }
 }
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::day::{Day, repo_root};
+
+// what the last `aoc bench` of a day measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    // how many "Part N:" answers it printed, 0 for a day that isnt solved yet
+    pub parts: usize,
+    pub median: Duration,
+}
+
+// tab separated year, day, parts and median in microseconds, one line per day
+// kept next to the submission log and out of git, timings are only true for one machine
+pub struct Timings {
+    path: PathBuf,
+    days: BTreeMap<(u32, u32), Timing>,
+}
+
+impl Timings {
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        // no file yet just means nothing was benched
+        let text = fs::read_to_string(&path).unwrap_or_default();
+
+        let mut days = BTreeMap::new();
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let bad = || format!("Bad line in {}: {}", path.display(), line);
+            let numbers: Vec<u64> = line
+                .split('\t')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| bad())?;
+            let [year, day, parts, micros] = numbers[..] else {
+                return Err(bad());
+            };
+            let timing = Timing {
+                parts: parts as usize,
+                median: Duration::from_micros(micros),
+            };
+            days.insert((year as u32, day as u32), timing);
+        }
+
+        Ok(Timings { path, days })
+    }
+
+    pub fn get(&self, year: u32, day: u32) -> Option<Timing> {
+        self.days.get(&(year, day)).copied()
+    }
+
+    // replaces whatever the day had before and writes the whole file back
+    fn record(&mut self, year: u32, day: u32, timing: Timing) -> Result<(), String> {
+        self.days.insert((year, day), timing);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let text: String = self
+            .days
+            .iter()
+            .map(|((year, day), t)| {
+                format!("{}\t{}\t{}\t{}\n", year, day, t.parts, t.median.as_micros())
+            })
+            .collect();
+        fs::write(&self.path, text)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+pub fn timings_path() -> PathBuf {
+    repo_root().join(".aoc/timings.tsv")
+}
+
+// runs each day `runs` times and records the median, `aoc readme` reads it from there
+// every day is written as soon as it's done so a failure halfway keeps the rest
+pub fn bench(days: &[Day], runs: usize) -> Result<(), String> {
+    let mut timings = Timings::load(timings_path())?;
+
+    for day in days {
+        eprintln!("Running {}", day.name());
+        day.build()?;
+
+        let mut times = Vec::new();
+        let mut parts = 0;
+        for _ in 0..runs.max(1) {
+            let run = day.run()?;
+            parts = run.answers.len();
+            times.push(run.elapsed);
+        }
+
+        times.sort();
+        let median = times[times.len() / 2];
+        println!("{}: {:.2?} over {} runs", day.name(), median, times.len());
+        timings.record(day.year, day.day, Timing { parts, median })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn recorded_timings_read_back() {
+        let path = env::temp_dir().join(format!("aoc-bench-{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut timings = Timings::load(path.clone()).unwrap();
+        assert_eq!(timings.get(2024, 1), None);
+
+        let solved = Timing {
+            parts: 2,
+            median: Duration::from_micros(1500),
+        };
+        let stub = Timing {
+            parts: 0,
+            median: Duration::from_micros(900),
+        };
+        timings.record(2024, 1, stub).unwrap();
+        timings.record(2024, 22, stub).unwrap();
+        // a later bench of the same day replaces the earlier one
+        timings.record(2024, 1, solved).unwrap();
+
+        let timings = Timings::load(path.clone()).unwrap();
+        assert_eq!(timings.get(2024, 1), Some(solved));
+        assert_eq!(timings.get(2024, 22), Some(stub));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "2024\t1\t2\t1500\n2024\t22\t0\t900\n"
+        );
+
+        fs::write(&path, "2024\t1\tlots\t1500\n").unwrap();
+        assert!(Timings::load(path.clone()).is_err());
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::meta::Meta;
+
+// one solution crate, e.g. 2024/day16
+#[derive(Debug, Clone)]
+pub struct Day {
+    pub year: u32,
+    pub day: u32,
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Run {
+    // (label, answer) for every "Part N: answer" line the day printed
+    pub answers: Vec<(String, String)>,
+    // every other line, e.g. the final frame a `--play` day prints when stdout isnt a terminal
+    pub rest: Vec<String>,
+    pub elapsed: Duration,
+}
+
+impl Run {
+    // the answer the day printed for `part`, if it printed one
+    pub fn answer(&self, part: u32) -> Option<&str> {
+        let label = format!("Part {}", part);
+        self.answers
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, answer)| answer.as_str())
+    }
+}
+
+impl Day {
+    pub fn new(year: u32, day: u32) -> Result<Self, String> {
+        let dir = repo_root()
+            .join(year.to_string())
+            .join(format!("day{}", day));
+        if !dir.join("Cargo.toml").exists() {
+            return Err(format!("No solution crate at {}", dir.display()));
+        }
+        Ok(Day { year, day, dir })
+    }
+
+    // every day crate in the repo, ordered by year then day
+    pub fn all() -> Vec<Day> {
+        let mut days = Vec::new();
+        let Ok(entries) = fs::read_dir(repo_root()) else {
+            return days;
+        };
+
+        for entry in entries.flatten() {
+            let Some(year) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            days.extend((1..=25).filter_map(|day| Day::new(year, day).ok()));
+        }
+
+        days.sort_by_key(|d| (d.year, d.day));
+        days
+    }
+
+    pub fn name(&self) -> String {
+        format!("{} day {}", self.year, self.day)
+    }
+
+    // builds in release mode, compiler output goes straight to the terminal
+    pub fn build(&self) -> Result<(), String> {
+        let status = Command::new("cargo")
+            .args(["build", "--release", "--quiet"])
+            .current_dir(&self.dir)
+            .status()
+            .map_err(|e| format!("Failed to start cargo: {}", e))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("Build failed for {}", self.name()))
+        }
+    }
+
+    // same build with the compiler output kept for the error, for when the terminal is busy
+    pub fn build_quietly(&self) -> Result<(), String> {
+        let output = Command::new("cargo")
+            .args(["build", "--release", "--quiet"])
+            .current_dir(&self.dir)
+            .output()
+            .map_err(|e| format!("Failed to start cargo: {}", e))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Build failed for {}\n{}",
+                self.name(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    // runs the already built binary from the day directory so `src/input.txt` resolves
+    pub fn run(&self) -> Result<Run, String> {
+        let run = self.run_with(&[], None, None)?;
+        Ok(run.expect("only a run with a timeout can be cut short"))
+    }
+
+    // `run` with extra arguments for the binary and `input` in place of `src/input.txt`
+    // Ok(None) when it took longer than `timeout` and got killed
+    pub fn run_with(
+        &self,
+        args: &[&str],
+        input: Option<&Path>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Run>, String> {
+        let binary = self
+            .dir
+            .join("target/release")
+            .join(format!("day{}", self.day));
+        let mut command = Command::new(&binary);
+        command.args(args).current_dir(&self.dir);
+        if let Some(input) = input {
+            command.env("AOC_INPUT", input);
+        }
+
+        let start = Instant::now();
+        let output = match timeout {
+            None => command.output().map(Some),
+            Some(timeout) => command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .and_then(|child| wait_with_timeout(child, timeout)),
+        }
+        .map_err(|e| format!("Failed to run {}: {}", binary.display(), e))?;
+        let elapsed = start.elapsed();
+
+        let Some(output) = output else {
+            return Ok(None);
+        };
+
+        if !output.status.success() {
+            return Err(format!(
+                "{} exited with {}\n{}",
+                self.name(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (answers, rest) = parse_answers(&stdout);
+        Ok(Some(Run {
+            answers,
+            rest,
+            elapsed,
+        }))
+    }
+
+    // the `[package.metadata.aoc]` table from the day's Cargo.toml
+    pub fn meta(&self) -> Result<Meta, String> {
+        let path = self.dir.join("Cargo.toml");
+        let manifest = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Meta::parse(&manifest).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // source files whose changes should trigger a rebuild
+    pub fn watched_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.dir.join("Cargo.toml")];
+        collect_files(&self.dir.join("src"), &mut files);
+        // most days pull in the shared crate too
+        collect_files(&repo_root().join("common/src"), &mut files);
+        files
+    }
+}
+
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("aoc crate lives inside the repo")
+        .to_path_buf()
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// polls so the child can be killed once the time is up, None when it was
+// the pipes are drained on their own threads so a chatty child cant fill them and stall
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> std::io::Result<Option<Output>> {
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut bytes).ok();
+            }
+            bytes
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+// picks the "Part 1: 123" lines out of a day's output, ignoring anything after
+// the answer such as the alloc-stats bracket, every other line comes back as is
+fn parse_answers(stdout: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut answers = Vec::new();
+    let mut rest = Vec::new();
+    for line in stdout.lines() {
+        match line.split_once(": ") {
+            Some((label, after)) if label.starts_with("Part ") => {
+                let answer = after.split("  [").next().unwrap_or(after).trim();
+                answers.push((label.to_string(), answer.to_string()));
+            }
+            _ => rest.push(line.to_string()),
+        }
+    }
+    (answers, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(script: &str) -> Child {
+        Command::new("sh")
+            .args(["-c", script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn slow_runs_get_killed() {
+        let output = wait_with_timeout(spawn("echo done"), Duration::from_secs(10)).unwrap();
+        assert_eq!(output.unwrap().stdout, b"done\n");
+
+        let start = Instant::now();
+        let output = wait_with_timeout(spawn("sleep 5"), Duration::from_millis(50)).unwrap();
+        assert!(output.is_none());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn answers_and_the_rest() {
+        let (answers, rest) = parse_answers("debug\nPart 1: 42  [12 allocs]\n#.#\nPart 2: x\n");
+        assert_eq!(
+            answers,
+            vec![
+                ("Part 1".to_string(), "42".to_string()),
+                ("Part 2".to_string(), "x".to_string())
+            ]
+        );
+        assert_eq!(rest, vec!["debug", "#.#"]);
+    }
+}
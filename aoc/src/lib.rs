@@ -0,0 +1,4 @@
+// the parts of the tool that other crates run days through, the python bindings among them
+pub mod day;
+pub mod meta;
+pub mod serve;
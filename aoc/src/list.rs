@@ -0,0 +1,54 @@
+use crate::day::Day;
+use crate::meta::Meta;
+
+// prints every day with its title, tags and params, or only the days with `tag`
+// so an earlier solution can be found when a similar puzzle turns up
+pub fn list(tag: Option<&str>) -> Result<(), String> {
+    let mut rows = Vec::new();
+    for day in Day::all() {
+        let meta = day.meta()?;
+        rows.push((day, meta));
+    }
+
+    let matching: Vec<&(Day, Meta)> = rows
+        .iter()
+        .filter(|(_, meta)| tag.is_none_or(|tag| meta.has_tag(tag)))
+        .collect();
+
+    if matching.is_empty() {
+        let mut known: Vec<&str> = rows
+            .iter()
+            .flat_map(|(_, meta)| meta.tags.iter().map(String::as_str))
+            .collect();
+        known.sort_unstable();
+        known.dedup();
+        return Err(format!(
+            "No days tagged {}, known tags: {}",
+            tag.unwrap_or_default(),
+            known.join(", ")
+        ));
+    }
+
+    let width = |text: fn(&Meta) -> String| {
+        matching
+            .iter()
+            .map(|(_, meta)| text(meta).chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let title = |meta: &Meta| meta.title.clone().unwrap_or_else(|| "-".to_string());
+    let tags = |meta: &Meta| meta.tags.join(", ");
+    let (title_width, tags_width) = (width(title), width(tags));
+
+    for (day, meta) in matching {
+        let line = format!(
+            "{:<12} {:<title_width$}  {:<tags_width$}  {}",
+            day.name(),
+            title(meta),
+            tags(meta),
+            meta.params.join(", "),
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::env;
+use std::process;
+use std::time::Duration;
+
+mod bench;
+mod list;
+mod readme;
+mod submit;
+mod tui;
+mod watch;
+
+use aoc::{day, meta, serve};
+use day::Day;
+
+const USAGE: &str = "Usage:
+  aoc list [--tag <tag>]                 show every day's title, tags and params, or only the tagged ones
+  aoc watch --year <year> --day <day>    rebuild and rerun a day whenever its files change
+  aoc bench [--year <year> --day <day>] [--runs <n>]
+                                         time every day, or one, and record the medians
+  aoc readme                             rewrite the progress table in README.md from the records
+  aoc submit --year <year> --day <day> --part <part>
+                                         run a day and send one answer (needs AOC_SESSION)
+  aoc tui                                browse the days, run them and look at their inputs and grids
+  aoc serve [--port <port>] [--max-size <bytes>] [--timeout <secs>]
+                                         answer POST /<year>/<day>/part<n> on 127.0.0.1 with JSON,
+                                         run_ms times the whole day binary, both parts included";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("list") => list::list(flag(&args[1..], "--tag")),
+        Some("watch") => selected_day(&args[1..]).and_then(|day| watch::watch(&day)),
+        Some("bench") => {
+            let runs = flag(&args[1..], "--runs").map_or(Ok(5), str::parse);
+            let days = match (flag(&args[1..], "--year"), flag(&args[1..], "--day")) {
+                (None, None) => Ok(Day::all()),
+                _ => selected_day(&args[1..]).map(|day| vec![day]),
+            };
+            match (days, runs) {
+                (Ok(days), Ok(runs)) => bench::bench(&days, runs),
+                (Err(e), _) => Err(e),
+                _ => Err(USAGE.to_string()),
+            }
+        }
+        Some("readme") => readme::update(),
+        Some("submit") => {
+            let part = flag(&args[1..], "--part").and_then(|s| s.parse().ok());
+            match (selected_day(&args[1..]), part) {
+                (Ok(day), Some(part @ (1 | 2))) => submit::submit(&day, part),
+                (Err(e), _) => Err(e),
+                _ => Err(USAGE.to_string()),
+            }
+        }
+        Some("tui") => tui::tui(),
+        Some("serve") => {
+            serve_options(&args[1..]).and_then(|(port, limits)| serve::serve(port, limits))
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+// looks up `--name value` in the argument list
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn serve_options(args: &[String]) -> Result<(u16, serve::Limits), String> {
+    let number = |name: &str, default: u64| {
+        flag(args, name).map_or(Ok(default), |s| s.parse().map_err(|_| USAGE.to_string()))
+    };
+    let defaults = serve::Limits::default();
+    let port = number("--port", 8024)?;
+    let limits = serve::Limits {
+        max_body: number("--max-size", defaults.max_body as u64)? as usize,
+        timeout: Duration::from_secs(number("--timeout", defaults.timeout.as_secs())?),
+        ..defaults
+    };
+    let port = u16::try_from(port).map_err(|_| USAGE.to_string())?;
+    Ok((port, limits))
+}
+
+fn selected_day(args: &[String]) -> Result<Day, String> {
+    let year = flag(args, "--year").and_then(|s| s.parse().ok());
+    let day = flag(args, "--day").and_then(|s| s.parse().ok());
+    match (year, day) {
+        (Some(year), Some(day)) => Day::new(year, day),
+        _ => Err(USAGE.to_string()),
+    }
+}
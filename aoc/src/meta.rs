@@ -0,0 +1,170 @@
+// what a day declares about itself in the `[package.metadata.aoc]` table of its Cargo.toml
+//   title = "Reindeer Maze"
+//   tags = ["grid", "dijkstra"]
+//   params = ["--render <file>"]
+// cargo ignores the table, and since this crate has no toml parser only that much
+// toml is understood: one key per line, a string or a one line array of strings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meta {
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    // command line arguments the binary understands, as they'd appear in a usage line
+    pub params: Vec<String>,
+}
+
+const TABLE: &str = "[package.metadata.aoc]";
+
+impl Meta {
+    // a manifest without the table is fine and gives empty metadata
+    pub fn parse(manifest: &str) -> Result<Meta, String> {
+        let mut meta = Meta::default();
+        let mut lines = manifest.lines().map(str::trim);
+        if !lines.any(|line| line == TABLE) {
+            return Ok(meta);
+        }
+
+        for line in lines.take_while(|line| !line.starts_with('[')) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Expected `key = value` in {}, got {}", TABLE, line))?;
+
+            match key.trim() {
+                "title" => meta.title = Some(parse_string(value.trim())?),
+                "tags" => meta.tags = parse_array(value.trim())?,
+                "params" => meta.params = parse_array(value.trim())?,
+                other => return Err(format!("Unknown key {} in {}", other, TABLE)),
+            }
+        }
+        Ok(meta)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+fn parse_string(value: &str) -> Result<String, String> {
+    match read_string(value)? {
+        (s, "") => Ok(s),
+        (_, rest) => Err(format!("Unexpected {} after string", rest)),
+    }
+}
+
+fn parse_array(value: &str) -> Result<Vec<String>, String> {
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or_else(|| format!("Expected a one line array, got {}", value))?;
+
+    let mut items = Vec::new();
+    let mut rest = inner.trim();
+    while !rest.is_empty() {
+        let (item, after) = read_string(rest)?;
+        items.push(item);
+        rest = match after.strip_prefix(',') {
+            Some(after) => after.trim_start(),
+            None if after.is_empty() => after,
+            None => return Err(format!("Expected a comma before {}", after)),
+        };
+    }
+    Ok(items)
+}
+
+// a basic quoted string off the front of `s`, and whatever follows it
+fn read_string(s: &str) -> Result<(String, &str), String> {
+    let body = s
+        .strip_prefix('"')
+        .ok_or_else(|| format!("Expected a quoted string, got {}", s))?;
+
+    let mut out = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => return Ok((out, body[i + 1..].trim_start())),
+            '\\' => match chars.next() {
+                Some((_, '"')) => out.push('"'),
+                Some((_, '\\')) => out.push('\\'),
+                Some((_, 'n')) => out.push('\n'),
+                _ => return Err(format!("Unsupported escape in {}", s)),
+            },
+            _ => out.push(ch),
+        }
+    }
+    Err(format!("Unterminated string {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_aoc_table_only() {
+        let manifest = r#"
+[package]
+name = "day16"
+title = "not this one"
+
+[package.metadata.aoc]
+# the maze one
+title = "Reindeer \"Maze\""
+tags = ["grid", "dijkstra",]
+params = [ "--generate <size> [seed]" , "--render <file>" ]
+
+[dependencies]
+common = { path = "../../common" }
+"#;
+        assert_eq!(
+            Meta::parse(manifest).unwrap(),
+            Meta {
+                title: Some("Reindeer \"Maze\"".to_string()),
+                tags: vec!["grid".to_string(), "dijkstra".to_string()],
+                params: vec![
+                    "--generate <size> [seed]".to_string(),
+                    "--render <file>".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn missing_table_and_empty_arrays() {
+        assert_eq!(
+            Meta::parse("[package]\nname = \"day22\"\n").unwrap(),
+            Meta::default()
+        );
+        let meta =
+            Meta::parse("[package.metadata.aoc]\ntitle = \"LAN Party\"\ntags = []\n").unwrap();
+        assert!(meta.tags.is_empty());
+        assert!(!meta.has_tag("graph"));
+    }
+
+    #[test]
+    fn rejects_typos_and_unsupported_toml() {
+        for bad in [
+            "tag = [\"grid\"]",
+            "tags = \"grid\"",
+            "tags = [\"grid\", dp]",
+            "tags = [\"grid\",",
+            "title = \"open",
+            "title = 'single'",
+            "title = \"a\" \"b\"",
+        ] {
+            let manifest = format!("[package.metadata.aoc]\n{}\n", bad);
+            assert!(Meta::parse(&manifest).is_err(), "{}", bad);
+        }
+    }
+
+    // keeps the tables in the day crates from rotting
+    #[test]
+    fn every_day_declares_a_title() {
+        for day in crate::day::Day::all() {
+            let meta = day
+                .meta()
+                .unwrap_or_else(|e| panic!("{}: {}", day.name(), e));
+            assert!(meta.title.is_some(), "{} has no title", day.name());
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::time::Duration;
+
+use crate::bench::{self, Timings};
+use crate::day::{Day, repo_root};
+use crate::submit::History;
+
+const START: &str = "<!-- progress:start -->";
+const END: &str = "<!-- progress:end -->";
+
+struct Row {
+    day: Day,
+    // None when there is no submission log to say what was accepted
+    stars: Option<usize>,
+    median: Option<Duration>,
+}
+
+// rewrites the progress table in README.md from what was recorded, nothing is run here
+// stars are the parts `aoc submit` got accepted, printing an answer isnt enough
+// runtimes are the medians `aoc bench` measured last
+pub fn update() -> Result<(), String> {
+    let history = History::load(repo_root().join(".aoc/submissions.tsv"))?;
+    let timings = Timings::load(bench::timings_path())?;
+    let rows: Vec<Row> = Day::all()
+        .into_iter()
+        .map(|day| row(day, &history, &timings))
+        .collect();
+
+    let path = repo_root().join("README.md");
+    let existing = fs::read_to_string(&path)
+        .unwrap_or_else(|_| format!("# Advent of Code\n\n{}\n{}\n", START, END));
+
+    let (before, rest) = existing
+        .split_once(START)
+        .ok_or("README.md has no progress:start marker")?;
+    let (_, after) = rest
+        .split_once(END)
+        .ok_or("README.md has no progress:end marker")?;
+
+    let updated = format!("{}{}\n{}{}{}", before, START, table(&rows), END, after);
+    fs::write(&path, updated).map_err(|e| format!("Failed to write README.md: {}", e))?;
+
+    println!("Updated {}", path.display());
+    Ok(())
+}
+
+fn row(day: Day, history: &History, timings: &Timings) -> Row {
+    let timing = timings.get(day.year, day.day);
+    // a day that prints no answers cant have earned any stars, log or not
+    let stars = match timing {
+        Some(t) if t.parts == 0 => Some(0),
+        _ => history
+            .exists()
+            .then(|| history.accepted(day.year, day.day)),
+    };
+    let median = timing.filter(|t| t.parts > 0).map(|t| t.median);
+    Row { day, stars, median }
+}
+
+fn table(rows: &[Row]) -> String {
+    let mut out = String::from("| Year | Day | Stars | Median runtime |\n");
+    out.push_str("|------|----:|-------|---------------:|\n");
+
+    for row in rows {
+        let median = row.median.map_or("-".to_string(), |t| format!("{:.2?}", t));
+        let stars = match row.stars {
+            None => "unknown".to_string(),
+            Some(0) => "0".to_string(),
+            Some(n) => "⭐".repeat(n),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            row.day.year, row.day.day, stars, median
+        ));
+    }
+
+    let total: Option<usize> = rows.iter().map(|r| r.stars).sum();
+    let total = total.map_or("unknown".to_string(), |n| n.to_string());
+    out.push_str(&format!("\nTotal stars: {}\n", total));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn solved(day: u32, stars: Option<usize>) -> Row {
+        Row {
+            day: Day::new(2024, day).unwrap(),
+            stars,
+            median: Some(Duration::from_millis(3)),
+        }
+    }
+
+    #[test]
+    fn stars_come_from_the_log() {
+        let out = table(&[solved(1, Some(2)), solved(2, Some(1))]);
+        assert!(out.contains("| 2024 | 1 | ⭐⭐ | 3.00ms |"));
+        assert!(out.contains("| 2024 | 2 | ⭐ | 3.00ms |"));
+        assert!(out.ends_with("Total stars: 3\n"));
+
+        // without a log nothing is known, printing an answer doesnt count
+        let out = table(&[solved(1, None), solved(2, Some(1))]);
+        assert!(out.contains("| 2024 | 1 | unknown | 3.00ms |"));
+        assert!(out.ends_with("Total stars: unknown\n"));
+    }
+
+    #[test]
+    fn days_without_answers_have_no_stars() {
+        let dir = env::temp_dir().join(format!("aoc-readme-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("timings.tsv"),
+            "2024\t1\t2\t1500\n2024\t22\t0\t900\n",
+        )
+        .unwrap();
+        let timings = Timings::load(dir.join("timings.tsv")).unwrap();
+        let no_log = History::load(dir.join("submissions.tsv")).unwrap();
+
+        let rows = [
+            row(Day::new(2024, 1).unwrap(), &no_log, &timings),
+            row(Day::new(2024, 22).unwrap(), &no_log, &timings),
+            row(Day::new(2024, 2).unwrap(), &no_log, &timings),
+        ];
+        let out = table(&rows);
+        assert!(out.contains("| 2024 | 1 | unknown | 1.50ms |"));
+        assert!(out.contains("| 2024 | 22 | 0 | - |"));
+        // never benched, so no runtime either
+        assert!(out.contains("| 2024 | 2 | unknown | - |"));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::day::{Day, Run};
+
+// a small HTTP server for running days on inputs sent from elsewhere
+//   POST /<year>/<day>/part<n>   body is the puzzle input
+// answers {"year":2024,"day":16,"part":2,"answer":"1024","run_ms":12.3}
+// and anything that goes wrong as {"error":"..."} with a matching status
+// it only listens on 127.0.0.1, days run out of process like everywhere else in this tool
+// so run_ms is the whole binary, both parts and reading the input included, not the one part
+
+// request line plus headers
+const MAX_HEAD: usize = 16 * 1024;
+// connections past this get a 503 straight away
+const MAX_CONNECTIONS: usize = 16;
+// most of a refused body that still gets read off the socket before closing
+const MAX_DRAIN: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    // largest input accepted, in bytes
+    pub max_body: usize,
+    // how long a day gets to answer, the build on the first request for a day doesnt count
+    pub timeout: Duration,
+    // how long a client may take to send its request
+    pub read_timeout: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_body: 1024 * 1024,
+            timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+// what the server calls to get a day's answers for an input, Ok(None) when it ran out of time
+pub trait Solve: Send + Sync {
+    fn solve(&self, day: &Day, input: &str, timeout: Duration) -> Result<Option<Run>, String>;
+}
+
+// builds each day the first time it's asked for, then runs it on a temp copy of the input
+#[derive(Default)]
+pub struct Runner {
+    builds: Builds,
+    requests: AtomicUsize,
+}
+
+// one lock per day, held through that day's build so two requests dont build it at once
+// while requests for other days go ahead, the map itself is only locked to look one up
+#[derive(Default)]
+struct Builds {
+    days: Mutex<HashMap<(u32, u32), BuildLock>>,
+}
+
+// true once the day is built
+type BuildLock = Arc<Mutex<bool>>;
+
+impl Builds {
+    fn ensure(
+        &self,
+        key: (u32, u32),
+        build: impl FnOnce() -> Result<(), String>,
+    ) -> Result<(), String> {
+        let lock = Arc::clone(self.days.lock().unwrap().entry(key).or_default());
+        let mut built = lock.lock().unwrap();
+        if !*built {
+            build()?;
+            *built = true;
+        }
+        Ok(())
+    }
+}
+
+impl Runner {
+    // builds the day unless an earlier request already did
+    pub fn build(&self, day: &Day) -> Result<(), String> {
+        self.builds
+            .ensure((day.year, day.day), || day.build_quietly())
+    }
+
+    // runs the built day on a temp copy of `input`, Ok(None) when it ran out of time
+    pub fn run(
+        &self,
+        day: &Day,
+        input: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Run>, String> {
+        let n = self.requests.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("aoc-serve-{}-{}.txt", std::process::id(), n));
+        fs::write(&path, input)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        let run = day.run_with(&[], Some(&path), timeout);
+        fs::remove_file(&path).ok();
+        run
+    }
+}
+
+impl Solve for Runner {
+    fn solve(&self, day: &Day, input: &str, timeout: Duration) -> Result<Option<Run>, String> {
+        self.build(day)?;
+        self.run(day, input, Some(timeout))
+    }
+}
+
+pub fn serve(port: u16, limits: Limits) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("Listening on http://{}", address);
+
+    listen(listener, limits, Arc::new(Runner::default()));
+    Ok(())
+}
+
+// one thread per connection, one request per connection
+fn listen(listener: TcpListener, limits: Limits, solver: Arc<dyn Solve>) {
+    let open = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            respond(
+                &mut stream,
+                &Response::error(503, "Too many requests at once"),
+            );
+            continue;
+        }
+
+        let (open, solver) = (Arc::clone(&open), Arc::clone(&solver));
+        thread::spawn(move || {
+            let response = handle(&mut stream, limits, solver.as_ref());
+            respond(&mut stream, &response);
+            close(stream);
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+}
+
+fn handle(stream: &mut TcpStream, limits: Limits, solver: &dyn Solve) -> Response {
+    stream.set_read_timeout(Some(limits.read_timeout)).ok();
+    stream.set_write_timeout(Some(limits.read_timeout)).ok();
+
+    let (day, part, input) = match read_request(stream, limits) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+
+    match solver.solve(&day, &input, limits.timeout) {
+        Ok(Some(run)) => match run.answer(part) {
+            Some(answer) => Response {
+                status: 200,
+                body: format!(
+                    "{{\"year\":{},\"day\":{},\"part\":{},\"answer\":{},\"run_ms\":{:.3}}}",
+                    day.year,
+                    day.day,
+                    part,
+                    json_string(answer),
+                    run.elapsed.as_secs_f64() * 1000.0
+                ),
+            },
+            None => Response::error(
+                500,
+                &format!("{} printed no answer for part {}", day.name(), part),
+            ),
+        },
+        Ok(None) => Response::error(
+            504,
+            &format!("{} took longer than {:.2?}", day.name(), limits.timeout),
+        ),
+        Err(e) => Response::error(500, &e),
+    }
+}
+
+// checks the request line, the headers and the size before reading the body,
+// so nothing is run and nothing big is read for a request that would be turned down
+fn read_request(stream: &mut TcpStream, limits: Limits) -> Result<(Day, u32, String), Response> {
+    let mut reader = BufReader::new(
+        stream
+            .try_clone()
+            .map_err(|_| Response::error(500, "Broken connection"))?,
+    );
+
+    let mut head = Vec::new();
+    let mut read = 0;
+    loop {
+        let mut line = String::new();
+        let n = (&mut reader)
+            .take((MAX_HEAD - read) as u64)
+            .read_line(&mut line)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                    Response::error(408, "Timed out reading the request")
+                }
+                _ => Response::error(400, "Unreadable request"),
+            })?;
+        read += n;
+        if !line.ends_with('\n') {
+            return Err(if read >= MAX_HEAD {
+                Response::error(431, "Request headers too large")
+            } else {
+                Response::error(400, "Incomplete request")
+            });
+        }
+        let line = line.trim_end().to_string();
+        if line.is_empty() {
+            break;
+        }
+        head.push(line);
+    }
+
+    let request_line = head
+        .first()
+        .ok_or_else(|| Response::error(400, "Empty request"))?;
+    let mut words = request_line.split_whitespace();
+    let (method, target) = match (words.next(), words.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return Err(Response::error(400, "Bad request line")),
+    };
+
+    let (day, part) = route(target)?;
+    if method != "POST" {
+        return Err(Response::error(405, "Only POST is supported"));
+    }
+
+    let header = |name: &str| {
+        head[1..].iter().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+    let length: usize = header("content-length")
+        .ok_or_else(|| Response::error(411, "Content-Length is required"))?
+        .parse()
+        .map_err(|_| Response::error(400, "Bad Content-Length"))?;
+    if length > limits.max_body {
+        return Err(Response::error(
+            413,
+            &format!(
+                "Input is {} bytes, the limit is {}",
+                length, limits.max_body
+            ),
+        ));
+    }
+
+    // curl holds back bigger bodies until it hears this
+    if header("expect").is_some_and(|e| e.eq_ignore_ascii_case("100-continue")) {
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").ok();
+    }
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Response::error(408, "Timed out reading the input"))?;
+    let input = String::from_utf8(body).map_err(|_| Response::error(400, "Input must be UTF-8"))?;
+
+    Ok((day, part, input))
+}
+
+// "/2024/16/part2"
+fn route(target: &str) -> Result<(Day, u32), Response> {
+    let not_found = || Response::error(404, "Expected /<year>/<day>/part<1|2>");
+
+    let parts: Vec<&str> = target.trim_matches('/').split('/').collect();
+    let [year, day, part] = parts[..] else {
+        return Err(not_found());
+    };
+    let (Ok(year), Ok(day)) = (year.parse(), day.parse()) else {
+        return Err(not_found());
+    };
+    let part = match part {
+        "part1" => 1,
+        "part2" => 2,
+        _ => return Err(not_found()),
+    };
+
+    let day = Day::new(year, day).map_err(|e| Response::error(404, &e))?;
+    Ok((day, part))
+}
+
+fn respond(stream: &mut TcpStream, response: &Response) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    };
+    let allow = match response.status {
+        405 => "Allow: POST\r\n",
+        _ => "",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        allow,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).ok();
+    stream.write_all(response.body.as_bytes()).ok();
+}
+
+// reads whatever the client still had coming before closing, a refused request can
+// leave a body unread and closing on unread data resets the connection before
+// the client gets to read the response
+fn close(mut stream: TcpStream) {
+    stream.shutdown(Shutdown::Write).ok();
+    let mut rest = (&mut stream).take(MAX_DRAIN);
+    io::copy(&mut rest, &mut io::sink()).ok();
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    // answers with the input length, or sleeps past the timeout for "slow", or fails for "crash"
+    struct Fake {
+        calls: Mutex<mpsc::Sender<String>>,
+    }
+
+    impl Solve for Fake {
+        fn solve(&self, _: &Day, input: &str, _: Duration) -> Result<Option<Run>, String> {
+            self.calls.lock().unwrap().send(input.to_string()).unwrap();
+            match input {
+                "slow" => Ok(None),
+                "crash" => Err("day1 exited with exit status: 101".to_string()),
+                "quiet" => Ok(Some(Run {
+                    answers: Vec::new(),
+                    rest: Vec::new(),
+                    elapsed: Duration::ZERO,
+                })),
+                _ => Ok(Some(Run {
+                    answers: vec![
+                        ("Part 1".to_string(), input.len().to_string()),
+                        ("Part 2".to_string(), format!("\"{}\"", input.trim())),
+                    ],
+                    rest: Vec::new(),
+                    elapsed: Duration::from_micros(1500),
+                })),
+            }
+        }
+    }
+
+    fn start(limits: Limits, solver: Arc<dyn Solve>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || listen(listener, limits, solver));
+        address
+    }
+
+    fn start_fake(limits: Limits) -> (String, mpsc::Receiver<String>) {
+        let (tx, rx) = mpsc::channel();
+        let fake = Fake {
+            calls: Mutex::new(tx),
+        };
+        (start(limits, Arc::new(fake)), rx)
+    }
+
+    // sends raw bytes and returns (status, body)
+    fn send(address: &str, request: &[u8]) -> (u16, String) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response[9..12].parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (status, body)
+    }
+
+    fn post(address: &str, path: &str, input: &str) -> (u16, String) {
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            input.len(),
+            input
+        );
+        send(address, request.as_bytes())
+    }
+
+    #[test]
+    fn answers_as_json() {
+        let (address, calls) = start_fake(Limits::default());
+
+        let (status, body) = post(&address, "/2024/16/part1", "abc\n");
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            r#"{"year":2024,"day":16,"part":1,"answer":"4","run_ms":1.500}"#
+        );
+        assert_eq!(calls.recv().unwrap(), "abc\n");
+
+        // answers are escaped
+        let (status, body) = post(&address, "/2024/16/part2", "abc\n");
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""answer":"\"abc\"""#));
+    }
+
+    #[test]
+    fn turns_down_bad_requests_without_running() {
+        let limits = Limits {
+            max_body: 8,
+            ..Limits::default()
+        };
+        let (address, calls) = start_fake(limits);
+
+        assert_eq!(post(&address, "/2019/1/part1", "x").0, 404);
+        assert_eq!(post(&address, "/2024/16/part3", "x").0, 404);
+        assert_eq!(post(&address, "/2024/16", "x").0, 404);
+        assert_eq!(
+            send(&address, b"GET /2024/16/part1 HTTP/1.1\r\n\r\n").0,
+            405
+        );
+        assert_eq!(
+            send(&address, b"POST /2024/16/part1 HTTP/1.1\r\n\r\n").0,
+            411
+        );
+
+        let (status, body) = post(&address, "/2024/16/part1", "way too long");
+        assert_eq!(status, 413);
+        assert_eq!(body, r#"{"error":"Input is 12 bytes, the limit is 8"}"#);
+
+        let request = b"POST /2024/16/part1 HTTP/1.1\r\nContent-Length: 2\r\n\r\n\xff\xfe";
+        assert_eq!(send(&address, request).0, 400);
+
+        let huge = format!(
+            "POST /2024/16/part1 HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_HEAD)
+        );
+        assert_eq!(send(&address, huge.as_bytes()).0, 431);
+
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[test]
+    fn reports_timeouts_and_failures() {
+        let limits = Limits {
+            read_timeout: Duration::from_millis(100),
+            ..Limits::default()
+        };
+        let (address, _calls) = start_fake(limits);
+
+        assert_eq!(post(&address, "/2024/16/part1", "slow").0, 504);
+        let (status, body) = post(&address, "/2024/16/part1", "crash");
+        assert_eq!(status, 500);
+        assert!(body.contains("exit status: 101"));
+        let (status, body) = post(&address, "/2024/16/part1", "quiet");
+        assert_eq!(status, 500);
+        assert!(body.contains("printed no answer for part 1"));
+
+        // a client that promises more input than it sends
+        let request = b"POST /2024/16/part1 HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc";
+        assert_eq!(send(&address, request).0, 408);
+    }
+
+    #[test]
+    fn a_slow_build_only_holds_up_its_own_day() {
+        let builds = Arc::new(Builds::default());
+        let (started, wait_for_start) = mpsc::channel();
+        let (finish, wait_for_finish) = mpsc::channel::<()>();
+
+        let slow = {
+            let builds = Arc::clone(&builds);
+            thread::spawn(move || {
+                builds.ensure((2024, 16), || {
+                    started.send(()).unwrap();
+                    wait_for_finish.recv().unwrap();
+                    Ok(())
+                })
+            })
+        };
+        wait_for_start.recv().unwrap();
+
+        // another day builds and comes back while day 16 is still building
+        let mut ran = false;
+        builds
+            .ensure((2024, 1), || {
+                ran = true;
+                Ok(())
+            })
+            .unwrap();
+        assert!(ran);
+
+        finish.send(()).unwrap();
+        slow.join().unwrap().unwrap();
+
+        // a day is only built once, a failed build is tried again next time
+        builds.ensure((2024, 16), || panic!("built twice")).unwrap();
+        assert!(
+            builds
+                .ensure((2024, 2), || Err("broken".to_string()))
+                .is_err()
+        );
+        let mut retried = false;
+        builds
+            .ensure((2024, 2), || {
+                retried = true;
+                Ok(())
+            })
+            .unwrap();
+        assert!(retried);
+    }
+
+    #[test]
+    fn runs_a_real_day() {
+        let address = start(Limits::default(), Arc::new(Runner::default()));
+        let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+
+        let (status, body) = post(&address, "/2024/1/part1", example);
+        assert_eq!(status, 200, "{}", body);
+        assert!(body.starts_with(r#"{"year":2024,"day":1,"part":1,"answer":"11","run_ms":"#));
+
+        let (status, body) = post(&address, "/2024/1/part2", example);
+        assert_eq!(status, 200, "{}", body);
+        assert!(body.contains(r#""answer":"31""#));
+    }
+}
@@ -0,0 +1,535 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::day::{Day, repo_root};
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    // the site refused the answer because the last one was too recent
+    TooSoon,
+    // the part is already solved or locked
+    WrongLevel,
+    Unknown,
+}
+
+impl Verdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::TooSoon => "too-soon",
+            Verdict::WrongLevel => "wrong-level",
+            Verdict::Unknown => "unknown",
+        }
+    }
+
+    fn parse(s: &str) -> Verdict {
+        match s {
+            "correct" => Verdict::Correct,
+            "wrong" => Verdict::Wrong,
+            "too-high" => Verdict::TooHigh,
+            "too-low" => Verdict::TooLow,
+            "too-soon" => Verdict::TooSoon,
+            "wrong-level" => Verdict::WrongLevel,
+            _ => Verdict::Unknown,
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow)
+    }
+}
+
+// one line of the submission log
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub time: u64,
+    pub year: u32,
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub verdict: Verdict,
+    // unix time before which the site wont take another answer for this day
+    pub wait_until: u64,
+}
+
+// tab separated log of every answer sent, kept next to the repo and out of git
+pub struct History {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl History {
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        // no log yet just means nothing has been sent
+        let text = fs::read_to_string(&path).unwrap_or_default();
+
+        let entries = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                if fields.len() != 7 {
+                    return Err(format!("Bad line in {}: {}", path.display(), line));
+                }
+                let number = |s: &str| s.parse().map_err(|_| format!("Bad number {}", s));
+                Ok(Entry {
+                    time: number(fields[0])?,
+                    year: number(fields[1])? as u32,
+                    day: number(fields[2])? as u32,
+                    part: number(fields[3])? as u32,
+                    answer: fields[4].to_string(),
+                    verdict: Verdict::parse(fields[5]),
+                    wait_until: number(fields[6])?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(History { path, entries })
+    }
+
+    fn append(&mut self, entry: Entry) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {}", self.path.display(), e))?;
+
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            entry.time,
+            entry.year,
+            entry.day,
+            entry.part,
+            entry.answer,
+            entry.verdict.as_str(),
+            entry.wait_until
+        )
+        .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    // whether a log was ever written, an empty history and no history read the same otherwise
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    // how many parts of this day the site accepted an answer for
+    pub fn accepted(&self, year: u32, day: u32) -> usize {
+        let mut parts: Vec<u32> = self
+            .entries
+            .iter()
+            .filter(|e| e.year == year && e.day == day && e.verdict == Verdict::Correct)
+            .map(|e| e.part)
+            .collect();
+        parts.sort();
+        parts.dedup();
+        parts.len()
+    }
+
+    // reasons not to send this answer at all, based on what we already know
+    fn check(&self, year: u32, day: u32, part: u32, answer: &str, now: u64) -> Result<(), String> {
+        let same_part = || {
+            self.entries
+                .iter()
+                .filter(move |e| e.year == year && e.day == day && e.part == part)
+        };
+
+        if let Some(e) = same_part().find(|e| e.verdict == Verdict::Correct) {
+            return Err(format!(
+                "Already solved, the accepted answer was {}",
+                e.answer
+            ));
+        }
+
+        if let Some(e) = same_part().find(|e| e.answer == answer && e.verdict.is_wrong()) {
+            return Err(format!(
+                "{} was already rejected ({})",
+                answer,
+                e.verdict.as_str()
+            ));
+        }
+
+        // the site tells us which side a wrong number was on, so use that too
+        if let Ok(value) = answer.parse::<i128>() {
+            for e in same_part() {
+                let Ok(old) = e.answer.parse::<i128>() else {
+                    continue;
+                };
+                if e.verdict == Verdict::TooHigh && value >= old {
+                    return Err(format!(
+                        "{} is not below {} which was too high",
+                        answer, old
+                    ));
+                }
+                if e.verdict == Verdict::TooLow && value <= old {
+                    return Err(format!("{} is not above {} which was too low", answer, old));
+                }
+            }
+        }
+
+        // the wait applies to the whole day, not just one part
+        let wait_until = self
+            .entries
+            .iter()
+            .filter(|e| e.year == year && e.day == day)
+            .map(|e| e.wait_until)
+            .max()
+            .unwrap_or(0);
+        if wait_until > now {
+            return Err(format!("Wait {}s before answering again", wait_until - now));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Client {
+    pub base_url: String,
+    pub session: String,
+}
+
+impl Client {
+    // AOC_SESSION is the session cookie from the browser, AOC_BASE_URL is only for tests
+    pub fn from_env() -> Result<Self, String> {
+        let session = env::var("AOC_SESSION")
+            .map_err(|_| "Set AOC_SESSION to your adventofcode.com session cookie".to_string())?;
+        let base_url = env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Ok(Client { base_url, session })
+    }
+
+    // posts through curl so the tool doesnt need its own TLS stack
+    fn post_answer(&self, year: u32, day: u32, part: u32, answer: &str) -> Result<String, String> {
+        let url = format!("{}/{}/day/{}/answer", self.base_url, year, day);
+        let mut child = self
+            .curl(&url, part, answer)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start curl: {}", e))?;
+
+        // dropped straight after writing so curl sees the end of its config
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(self.curl_config().as_bytes())
+            .map_err(|e| format!("Failed to pass the session to curl: {}", e))?;
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Posting to {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // the session goes in through `-K -` on stdin, anything on the command line
+    // can be read by every other user on the machine through ps
+    fn curl(&self, url: &str, part: u32, answer: &str) -> Command {
+        let mut command = Command::new("curl");
+        command
+            .args(["--silent", "--show-error", "--max-time", "30"])
+            .args(["--config", "-"])
+            .args(["--data", &format!("level={}", part)])
+            .args(["--data-urlencode", &format!("answer={}", answer)])
+            .arg(url);
+        command
+    }
+
+    fn curl_config(&self) -> String {
+        let session = self.session.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("cookie = \"session={}\"\n", session)
+    }
+}
+
+// runs the day, then sends the answer for `part` unless the log says not to
+pub fn submit(day: &Day, part: u32) -> Result<(), String> {
+    day.build()?;
+    let run = day.run()?;
+    let label = format!("Part {}", part);
+    let answer = run
+        .answers
+        .iter()
+        .find(|(l, _)| *l == label)
+        .map(|(_, a)| a.clone())
+        .ok_or_else(|| format!("{} printed no answer for part {}", day.name(), part))?;
+
+    let client = Client::from_env()?;
+    let mut history = History::load(repo_root().join(".aoc/submissions.tsv"))?;
+    let verdict = send(&client, &mut history, day.year, day.day, part, &answer)?;
+    println!(
+        "{} part {}: {} -> {}",
+        day.name(),
+        part,
+        answer,
+        verdict.as_str()
+    );
+    Ok(())
+}
+
+pub fn send(
+    client: &Client,
+    history: &mut History,
+    year: u32,
+    day: u32,
+    part: u32,
+    answer: &str,
+) -> Result<Verdict, String> {
+    history.check(year, day, part, answer, now())?;
+
+    let body = client.post_answer(year, day, part, answer)?;
+    let (verdict, wait) = parse_response(&body);
+
+    history.append(Entry {
+        time: now(),
+        year,
+        day,
+        part,
+        answer: answer.to_string(),
+        verdict: verdict.clone(),
+        wait_until: now() + wait,
+    })?;
+    Ok(verdict)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+// reads the verdict and the wait time out of the response page
+fn parse_response(body: &str) -> (Verdict, u64) {
+    let verdict = if body.contains("That's the right answer") {
+        Verdict::Correct
+    } else if body.contains("your answer is too high") {
+        Verdict::TooHigh
+    } else if body.contains("your answer is too low") {
+        Verdict::TooLow
+    } else if body.contains("That's not the right answer") {
+        Verdict::Wrong
+    } else if body.contains("You gave an answer too recently") {
+        Verdict::TooSoon
+    } else if body.contains("You don't seem to be solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        Verdict::Unknown
+    };
+
+    (verdict, parse_wait(body))
+}
+
+// "Please wait one minute before trying again" or "You have 1m 12s left to wait"
+fn parse_wait(body: &str) -> u64 {
+    if let Some(i) = body.find("left to wait") {
+        let before = &body[..i];
+        let start = before
+            .rfind("You have ")
+            .map_or(0, |j| j + "You have ".len());
+        return before[start..]
+            .split_whitespace()
+            .filter_map(|part| {
+                let (number, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+                let number: u64 = number.parse().ok()?;
+                match unit {
+                    "s" => Some(number),
+                    "m" => Some(number * 60),
+                    "h" => Some(number * 3600),
+                    _ => None,
+                }
+            })
+            .sum();
+    }
+
+    if let Some(i) = body.find("Please wait ") {
+        let rest = &body[i + "Please wait ".len()..];
+        let amount = rest.split_whitespace().next().unwrap_or("");
+        let minutes = match amount {
+            "one" => 1,
+            "two" => 2,
+            "three" => 3,
+            "four" => 4,
+            "five" => 5,
+            other => other.parse().unwrap_or(1),
+        };
+        return minutes * 60;
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    // serves one canned page per request and hands back each request body
+    fn mock_server(pages: Vec<&'static str>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for page in pages {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut content_length = 0;
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+                tx.send(request).unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    page.len(),
+                    page
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn temp_history(name: &str) -> History {
+        let path = env::temp_dir().join(format!("aoc-submit-{}-{}.tsv", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        History::load(path).unwrap()
+    }
+
+    const WRONG: &str = "<article><p>That's not the right answer; your answer is too low. \
+        Please wait one minute before trying again.</p></article>";
+    const RIGHT: &str =
+        "<article><p>That's the right answer! You are one gold star closer.</p></article>";
+    const TOO_SOON: &str = "<article><p>You gave an answer too recently; \
+        you have to wait after submitting an answer before trying again. You have 1m 12s left to wait.</p></article>";
+
+    #[test]
+    fn posts_answer_and_logs_verdict() {
+        let (base_url, requests) = mock_server(vec![RIGHT]);
+        let client = Client {
+            base_url,
+            session: "abc".to_string(),
+        };
+        let mut history = temp_history("right");
+
+        let verdict = send(&client, &mut history, 2024, 16, 2, "1024").unwrap();
+        assert_eq!(verdict, Verdict::Correct);
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /2024/day/16/answer"));
+        assert!(request.contains("session=abc"));
+        assert!(request.ends_with("level=2&answer=1024"));
+
+        // reload from disk to make sure the log round trips
+        let reloaded = History::load(history.path.clone()).unwrap();
+        assert_eq!(reloaded.entries.len(), 1);
+        assert_eq!(reloaded.entries[0].verdict, Verdict::Correct);
+
+        // solved parts are never sent again
+        let err = send(&client, &mut history, 2024, 16, 2, "1024").unwrap_err();
+        assert!(err.contains("Already solved"));
+        assert!(reloaded.exists());
+        assert_eq!(reloaded.accepted(2024, 16), 1);
+        assert_eq!(reloaded.accepted(2024, 15), 0);
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn refuses_known_wrong_answers_and_waits() {
+        let (base_url, requests) = mock_server(vec![WRONG]);
+        let client = Client {
+            base_url,
+            session: "abc".to_string(),
+        };
+        let mut history = temp_history("wrong");
+
+        let verdict = send(&client, &mut history, 2025, 3, 1, "100").unwrap();
+        assert_eq!(verdict, Verdict::TooLow);
+        requests.recv().unwrap();
+        // a wrong answer earns no star
+        assert_eq!(history.accepted(2025, 3), 0);
+        assert!(history.entries[0].wait_until >= now() + 59);
+
+        // same answer, and anything not above a too-low answer, is refused without posting
+        let err = send(&client, &mut history, 2025, 3, 1, "100").unwrap_err();
+        assert!(err.contains("already rejected"));
+        let err = send(&client, &mut history, 2025, 3, 1, "90").unwrap_err();
+        assert!(err.contains("too low"));
+
+        // a new answer still has to wait out the timeout, for either part
+        let err = send(&client, &mut history, 2025, 3, 2, "200").unwrap_err();
+        assert!(err.starts_with("Wait"));
+        assert!(requests.try_recv().is_err());
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn session_stays_off_the_command_line() {
+        let client = Client {
+            base_url: "http://localhost".to_string(),
+            session: "s3cr3t\"x".to_string(),
+        };
+        let command = client.curl("http://localhost/2024/day/1/answer", 1, "42");
+        assert!(
+            command
+                .get_args()
+                .all(|arg| !arg.to_string_lossy().contains("s3cr3t"))
+        );
+        assert_eq!(client.curl_config(), "cookie = \"session=s3cr3t\\\"x\"\n");
+    }
+
+    #[test]
+    fn reads_wait_times() {
+        assert_eq!(parse_response(TOO_SOON), (Verdict::TooSoon, 72));
+        assert_eq!(parse_response(WRONG), (Verdict::TooLow, 60));
+        assert_eq!(
+            parse_wait("Please wait 5 minutes before trying again."),
+            300
+        );
+        assert_eq!(parse_response(RIGHT), (Verdict::Correct, 0));
+    }
+}
@@ -0,0 +1,405 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+use common::sim::{self, Colour, Frame, RawTerminal};
+
+use crate::day::{Day, Run};
+use crate::meta::Meta;
+
+// browse every day and run it without leaving the terminal
+// days run out of process like everywhere else in this tool, so "run a part"
+// runs the whole binary and shows that part's answer, the time covers both parts
+//   j k / arrows  move
+//   1 2           run and show one part
+//   r             run and show both
+//   g             final grid, for the days that take `--play`
+//   i             back to the input preview
+//   q             quit
+
+const LIST_WIDTH: usize = 34;
+// only the top of each input is kept for the preview
+const PREVIEW_LINES: usize = 200;
+// a `--play` day runs its simulation to the end before printing the grid
+const GRID_TIMEOUT: Duration = Duration::from_secs(30);
+const HELP: &str = "[j/k] move  [1/2] run a part  [r] both  [g] grid  [i] input  [q] quit";
+
+pub fn tui() -> Result<(), String> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err("aoc tui needs a terminal".to_string());
+    }
+
+    let mut browser = Browser::new(Day::all())?;
+    let _terminal = RawTerminal::enter();
+    let keys = sim::spawn_key_reader();
+    let mut decoder = Decoder::default();
+
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "\x1b[2J").unwrap();
+    loop {
+        draw(&mut stdout, &browser);
+
+        // a closed stdin counts as quitting
+        let Ok(byte) = keys.recv() else {
+            break;
+        };
+        let Some(key) = decoder.feed(byte) else {
+            continue;
+        };
+
+        match browser.key(key) {
+            Action::Nothing => {}
+            Action::Quit => break,
+            action => {
+                browser.message = format!("Running {}...", browser.current().day.name());
+                draw(&mut stdout, &browser);
+                browser.perform(action);
+            }
+        }
+    }
+    writeln!(stdout).unwrap();
+    Ok(())
+}
+
+fn draw(stdout: &mut impl Write, browser: &Browser) {
+    let (rows, cols) = RawTerminal::size().unwrap_or((24, 80));
+    write!(stdout, "\x1b[H{}", browser.render(rows, cols).to_ansi()).unwrap();
+    stdout.flush().unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Char(u8),
+}
+
+// turns the raw bytes into keys, arrows come in as ESC [ A and ESC [ B
+#[derive(Debug, Default)]
+struct Decoder {
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    fn feed(&mut self, byte: u8) -> Option<Key> {
+        self.pending.push(byte);
+        let key = match self.pending[..] {
+            [0x1b] | [0x1b, b'['] => return None,
+            [0x1b, b'[', b'A'] => Some(Key::Up),
+            [0x1b, b'[', b'B'] => Some(Key::Down),
+            // some other escape sequence, ignored
+            [0x1b, ..] => None,
+            _ => Some(Key::Char(byte)),
+        };
+        self.pending.clear();
+        key
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Nothing,
+    Quit,
+    // None runs both parts
+    Run(Option<u32>),
+    Grid,
+}
+
+// what the right hand side shows under the answers
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Panel {
+    Input,
+    Grid(Vec<String>),
+    Error(Vec<String>),
+}
+
+struct Entry {
+    day: Day,
+    meta: Meta,
+    preview: Vec<String>,
+    input_lines: usize,
+    run: Option<Run>,
+    // the part the last run was asked for, None for both
+    part: Option<u32>,
+}
+
+struct Browser {
+    entries: Vec<Entry>,
+    selected: usize,
+    panel: Panel,
+    message: String,
+}
+
+impl Browser {
+    fn new(days: Vec<Day>) -> Result<Self, String> {
+        if days.is_empty() {
+            return Err("No days found".to_string());
+        }
+
+        let entries = days
+            .into_iter()
+            .map(|day| {
+                let meta = day.meta()?;
+                let (preview, input_lines) = preview(&day);
+                Ok(Entry {
+                    day,
+                    meta,
+                    preview,
+                    input_lines,
+                    run: None,
+                    part: None,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Browser {
+            entries,
+            selected: 0,
+            panel: Panel::Input,
+            message: String::new(),
+        })
+    }
+
+    fn current(&self) -> &Entry {
+        &self.entries[self.selected]
+    }
+
+    fn key(&mut self, key: Key) -> Action {
+        match key {
+            Key::Up | Key::Char(b'k') => self.select(self.selected.saturating_sub(1)),
+            Key::Down | Key::Char(b'j') => self.select(self.selected + 1),
+            Key::Char(b'1') => return Action::Run(Some(1)),
+            Key::Char(b'2') => return Action::Run(Some(2)),
+            Key::Char(b'r') => return Action::Run(None),
+            Key::Char(b'g') if self.has_grid() => return Action::Grid,
+            Key::Char(b'g') => {
+                self.message = format!("{} has no --play grid", self.current().day.name());
+            }
+            Key::Char(b'i') => self.panel = Panel::Input,
+            Key::Char(b'q') => return Action::Quit,
+            Key::Char(_) => {}
+        }
+        Action::Nothing
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.entries.len() - 1);
+        if index != self.selected {
+            self.selected = index;
+            self.panel = Panel::Input;
+            self.message.clear();
+        }
+    }
+
+    fn has_grid(&self) -> bool {
+        self.current().meta.params.iter().any(|p| p == "--play")
+    }
+
+    // builds and runs the selected day, this is the slow part
+    fn perform(&mut self, action: Action) {
+        let entry = &mut self.entries[self.selected];
+        let result = entry.day.build_quietly().and_then(|_| match action {
+            Action::Grid => entry
+                .day
+                .run_with(&["--play"], None, Some(GRID_TIMEOUT))?
+                .ok_or_else(|| format!("The simulation took longer than {:?}", GRID_TIMEOUT)),
+            _ => entry.day.run(),
+        });
+
+        match result {
+            Ok(run) => {
+                self.message = format!("{} ran in {:.2?}", entry.day.name(), run.elapsed);
+                match action {
+                    Action::Run(part) => entry.part = part,
+                    _ => {
+                        entry.part = None;
+                        self.panel = Panel::Grid(run.rest.clone());
+                    }
+                }
+                entry.run = Some(run);
+            }
+            Err(e) => {
+                self.message = format!("{} failed", entry.day.name());
+                self.panel = Panel::Error(e.lines().map(String::from).collect());
+            }
+        }
+    }
+
+    fn render(&self, rows: usize, cols: usize) -> Frame {
+        // the last row is the status line
+        let height = rows.saturating_sub(1).max(2);
+        let mut frame = Frame::new(cols, height, ' ');
+        frame.write(0, 0, HELP, Some(Colour::Cyan));
+
+        // keeps the selected day on screen
+        let visible = height - 1;
+        let top = (self.selected + 1).saturating_sub(visible);
+        for (row, (i, entry)) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(top)
+            .take(visible)
+            .enumerate()
+        {
+            let title = entry.meta.title.as_deref().unwrap_or("-");
+            let line = format!("{:<12} {}", entry.day.name(), title);
+            let line: String = line.chars().take(LIST_WIDTH - 3).collect();
+            if i == self.selected {
+                frame.write(row + 1, 0, &format!("> {}", line), Some(Colour::Yellow));
+            } else {
+                frame.write(row + 1, 2, &line, None);
+            }
+        }
+
+        let entry = self.current();
+        let mut lines: Vec<(String, Option<Colour>)> = Vec::new();
+        let title = entry.meta.title.as_deref().unwrap_or("");
+        lines.push((
+            format!("{}  {}", entry.day.name(), title),
+            Some(Colour::Green),
+        ));
+        lines.push((format!("tags: {}", entry.meta.tags.join(", ")), None));
+        lines.push((String::new(), None));
+
+        for part in [1, 2] {
+            let label = format!("Part {}", part);
+            let answer = entry
+                .run
+                .as_ref()
+                .filter(|_| entry.part.is_none_or(|p| p == part))
+                .and_then(|run| run.answers.iter().find(|(l, _)| *l == label));
+            match answer {
+                Some((_, answer)) => {
+                    lines.push((format!("{}: {}", label, answer), Some(Colour::Green)))
+                }
+                None => lines.push((format!("{}: -", label), None)),
+            }
+        }
+        if let Some(run) = &entry.run {
+            lines.push((format!("Time: {:.2?} for the whole run", run.elapsed), None));
+        }
+        lines.push((String::new(), None));
+
+        match &self.panel {
+            Panel::Input => {
+                lines.push((
+                    format!("Input, {} lines:", entry.input_lines),
+                    Some(Colour::Cyan),
+                ));
+                lines.extend(entry.preview.iter().map(|l| (l.clone(), None)));
+            }
+            Panel::Grid(grid) => {
+                lines.push(("Final grid:".to_string(), Some(Colour::Cyan)));
+                lines.extend(grid.iter().map(|l| (l.clone(), None)));
+            }
+            Panel::Error(error) => {
+                lines.extend(error.iter().map(|l| (l.clone(), Some(Colour::Red))));
+            }
+        }
+
+        for (row, (line, colour)) in lines.iter().enumerate() {
+            frame.write(row + 1, LIST_WIDTH + 2, line, *colour);
+        }
+
+        frame.set_status(&self.message);
+        frame
+    }
+}
+
+// the top of the day's input and how many lines it has
+fn preview(day: &Day) -> (Vec<String>, usize) {
+    let path = day.dir.join("src/input.txt");
+    match common::input::try_load(&path.to_string_lossy()) {
+        Ok(input) => {
+            let lines = input.lines().take(PREVIEW_LINES).map(String::from);
+            (lines.collect(), input.lines().count())
+        }
+        Err(_) => (vec!["(no input)".to_string()], 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser() -> Browser {
+        let days = vec![
+            Day::new(2024, 1).unwrap(),
+            Day::new(2024, 6).unwrap(),
+            Day::new(2024, 16).unwrap(),
+        ];
+        Browser::new(days).unwrap()
+    }
+
+    #[test]
+    fn decodes_arrows_and_plain_keys() {
+        let mut decoder = Decoder::default();
+        let keys: Vec<Key> = b"j\x1b[A\x1b[B\x1b[Cq"
+            .iter()
+            .filter_map(|&b| decoder.feed(b))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![Key::Char(b'j'), Key::Up, Key::Down, Key::Char(b'q')]
+        );
+    }
+
+    #[test]
+    fn moves_within_the_list() {
+        let mut browser = browser();
+        assert_eq!(browser.key(Key::Up), Action::Nothing);
+        assert_eq!(browser.selected, 0);
+        for _ in 0..5 {
+            browser.key(Key::Char(b'j'));
+        }
+        assert_eq!(browser.selected, 2);
+        assert_eq!(browser.key(Key::Char(b'2')), Action::Run(Some(2)));
+        assert_eq!(browser.key(Key::Char(b'q')), Action::Quit);
+    }
+
+    #[test]
+    fn grid_only_for_play_days() {
+        let mut browser = browser();
+        assert_eq!(browser.key(Key::Char(b'g')), Action::Nothing);
+        assert!(browser.message.contains("no --play grid"));
+
+        browser.key(Key::Down);
+        assert_eq!(browser.key(Key::Char(b'g')), Action::Grid);
+    }
+
+    #[test]
+    fn shows_only_the_part_asked_for() {
+        let mut browser = browser();
+        browser.entries[0].run = Some(Run {
+            answers: vec![
+                ("Part 1".to_string(), "11".to_string()),
+                ("Part 2".to_string(), "31".to_string()),
+            ],
+            rest: Vec::new(),
+            elapsed: Duration::from_millis(2),
+        });
+        browser.entries[0].part = Some(2);
+        browser.panel = Panel::Grid(vec!["#.#".to_string()]);
+
+        let text = browser.render(12, 80).to_text();
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        assert!(lines[0].starts_with(HELP));
+        assert!(lines[1].starts_with("> 2024 day 1"));
+        assert!(lines[4].ends_with("Part 1: -"));
+        assert!(lines[5].ends_with("Part 2: 31"));
+        assert!(lines[6].ends_with("Time: 2.00ms for the whole run"));
+        assert!(lines[9].ends_with("#.#"));
+    }
+
+    #[test]
+    fn scrolls_to_keep_the_selection_visible() {
+        let mut browser = browser();
+        browser.key(Key::Down);
+        browser.key(Key::Down);
+        // room for the help line and two days
+        let text = browser.render(4, 60).to_text();
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        assert!(lines[1].starts_with("  2024 day 6"));
+        assert!(lines[2].starts_with("> 2024 day 16"));
+    }
+}
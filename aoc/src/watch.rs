@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::day::{Day, Run};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// rebuilds and reruns the day every time one of its files changes
+// polls modification times so it needs nothing beyond std
+pub fn watch(day: &Day) -> Result<(), String> {
+    println!("Watching {} ({})", day.name(), day.dir.display());
+
+    let mut seen = snapshot(day);
+    let mut previous = rerun(day, None);
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = snapshot(day);
+        if current == seen {
+            continue;
+        }
+        seen = current;
+
+        println!();
+        println!("Change detected, rebuilding {}", day.name());
+        if let Some(run) = rerun(day, previous.as_ref()) {
+            previous = Some(run);
+        }
+    }
+}
+
+fn snapshot(day: &Day) -> HashMap<PathBuf, SystemTime> {
+    day.watched_files()
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+fn rerun(day: &Day, previous: Option<&Run>) -> Option<Run> {
+    let result = day.build().and_then(|_| day.run());
+    match result {
+        Ok(run) => {
+            print_run(&run, previous);
+            Some(run)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+fn print_run(run: &Run, previous: Option<&Run>) {
+    for (label, answer) in &run.answers {
+        let before = previous.and_then(|p| p.answers.iter().find(|(l, _)| l == label));
+        match before {
+            Some((_, old)) if old == answer => println!("{}: {} (unchanged)", label, answer),
+            Some((_, old)) => println!("{}: {} (was {})", label, answer, old),
+            None => println!("{}: {}", label, answer),
+        }
+    }
+
+    match previous {
+        Some(p) => {
+            let change = run.elapsed.as_secs_f64() / p.elapsed.as_secs_f64() * 100.0 - 100.0;
+            println!(
+                "Time: {:.2?} (was {:.2?}, {:+.1}%)",
+                run.elapsed, p.elapsed, change
+            );
+        }
+        None => println!("Time: {:.2?}", run.elapsed),
+    }
+}
@@ -0,0 +1,356 @@
+use std::fmt;
+
+use crate::cells::CellMap;
+
+// cellular automata over a CellMap
+// a rule gets a cell's state and the states of its neighbours (in neighbourhood order)
+// and returns the new state, e.g. game of life on bools:
+//   |alive, around: &[bool]| matches!((alive, around.iter().filter(|&&n| n).count()), (true, 2) | (_, 3))
+// there are two ways to apply it
+//   step / run  synchronous, every cell sees the previous generation
+//   settle      queue based, cells change in place and only the neighbours of cells
+//               that changed get looked at again, much less work once things calm down.
+//               for rules where cells only ever change one way (removing, filling) it
+//               ends on the same grid as `run`
+
+// the cells a cell looks at, as (row, col) offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbourhood {
+    offsets: Vec<(isize, isize)>,
+}
+
+impl Neighbourhood {
+    // every cell within `radius` steps counting diagonals, 8 cells for radius 1
+    pub fn moore(radius: usize) -> Self {
+        Self::within(radius, |dr, dc| dr.abs().max(dc.abs()))
+    }
+
+    // every cell within `radius` orthogonal steps, 4 cells for radius 1
+    pub fn von_neumann(radius: usize) -> Self {
+        Self::within(radius, |dr, dc| dr.abs() + dc.abs())
+    }
+
+    pub fn offsets(offsets: impl IntoIterator<Item = (isize, isize)>) -> Self {
+        Neighbourhood {
+            offsets: offsets.into_iter().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    fn within(radius: usize, distance: fn(isize, isize) -> isize) -> Self {
+        let r = radius as isize;
+        let offsets = (-r..=r)
+            .flat_map(|dr| (-r..=r).map(move |dc| (dr, dc)))
+            .filter(|&(dr, dc)| (dr, dc) != (0, 0) && distance(dr, dc) <= r)
+            .collect();
+        Neighbourhood { offsets }
+    }
+}
+
+// what a neighbour past the edge of the grid looks like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges<S> {
+    // always this state
+    Fixed(S),
+    // the opposite edge, the grid is a torus
+    Wrap,
+}
+
+// what one generation did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Generation {
+    // counts from 1
+    pub index: usize,
+    // cells the rule was run on
+    pub evaluated: usize,
+    // cells whose state changed
+    pub changed: usize,
+}
+
+impl fmt::Display for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "generation {}: {} changed, {} evaluated",
+            self.index, self.changed, self.evaluated
+        )
+    }
+}
+
+pub struct Automaton<S, R> {
+    cells: CellMap<S>,
+    offsets: Vec<(isize, isize)>,
+    edges: Edges<S>,
+    rule: R,
+    generation: usize,
+}
+
+impl<S, R> Automaton<S, R>
+where
+    S: Copy + PartialEq + Default,
+    R: Fn(S, &[S]) -> S,
+{
+    // cells past the edge read as S::default() unless `with_edges` says otherwise
+    pub fn new(cells: CellMap<S>, neighbourhood: Neighbourhood, rule: R) -> Self {
+        Automaton {
+            cells,
+            offsets: neighbourhood.offsets,
+            edges: Edges::Fixed(S::default()),
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn with_edges(mut self, edges: Edges<S>) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    pub fn cells(&self) -> &CellMap<S> {
+        &self.cells
+    }
+
+    pub fn into_cells(self) -> CellMap<S> {
+        self.cells
+    }
+
+    // generations run so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // one synchronous generation
+    pub fn step(&mut self) -> Generation {
+        let mut next = self.cells.clone();
+        let mut around = Vec::with_capacity(self.offsets.len());
+        let mut changed = 0;
+
+        for r in 0..self.cells.rows() {
+            for c in 0..self.cells.cols() {
+                let state = self.cells[(r, c)];
+                self.gather((r, c), &mut around);
+                let new = (self.rule)(state, &around);
+                if new != state {
+                    next[(r, c)] = new;
+                    changed += 1;
+                }
+            }
+        }
+
+        self.cells = next;
+        self.generation += 1;
+        Generation {
+            index: self.generation,
+            evaluated: self.cells.rows() * self.cells.cols(),
+            changed,
+        }
+    }
+
+    // synchronous generations until one changes nothing or `limit` have run
+    // the generation that changed nothing is the last one returned
+    pub fn run(&mut self, limit: usize) -> Vec<Generation> {
+        let mut generations = Vec::new();
+        while generations.len() < limit {
+            let generation = self.step();
+            generations.push(generation);
+            if generation.changed == 0 {
+                break;
+            }
+        }
+        generations
+    }
+
+    // queue based updates until nothing changes
+    // a generation is one pass over the queue, which starts as every cell and after
+    // that holds the cells with a neighbour that changed in the pass before
+    pub fn settle(&mut self) -> Vec<Generation> {
+        let (rows, cols) = (self.cells.rows(), self.cells.cols());
+        let mut queue: Vec<(usize, usize)> = (0..rows)
+            .flat_map(|r| (0..cols).map(move |c| (r, c)))
+            .collect();
+        let mut queued = CellMap::new(rows, cols, true);
+        let mut around = Vec::with_capacity(self.offsets.len());
+        let mut generations = Vec::new();
+
+        while !queue.is_empty() {
+            let mut next_queue = Vec::new();
+            let mut changed = 0;
+
+            for &pos in &queue {
+                queued[pos] = false;
+                let state = self.cells[pos];
+                self.gather(pos, &mut around);
+                let new = (self.rule)(state, &around);
+                if new == state {
+                    continue;
+                }
+
+                self.cells[pos] = new;
+                changed += 1;
+                // the cells that have this one as a neighbour
+                for &(dr, dc) in &self.offsets {
+                    if let Some(watcher) = self.offset(pos, (-dr, -dc))
+                        && !queued[watcher]
+                    {
+                        queued[watcher] = true;
+                        next_queue.push(watcher);
+                    }
+                }
+            }
+
+            self.generation += 1;
+            generations.push(Generation {
+                index: self.generation,
+                evaluated: queue.len(),
+                changed,
+            });
+            queue = next_queue;
+        }
+        generations
+    }
+
+    fn gather(&self, pos: (usize, usize), around: &mut Vec<S>) {
+        around.clear();
+        for &delta in &self.offsets {
+            around.push(match (self.offset(pos, delta), self.edges) {
+                (Some(neighbour), _) => self.cells[neighbour],
+                (None, Edges::Fixed(outside)) => outside,
+                (None, Edges::Wrap) => unreachable!("wrapped offsets are always inside"),
+            });
+        }
+    }
+
+    // the cell `delta` away, None when that is past a fixed edge
+    fn offset(&self, (r, c): (usize, usize), (dr, dc): (isize, isize)) -> Option<(usize, usize)> {
+        let (rows, cols) = (self.cells.rows() as isize, self.cells.cols() as isize);
+        let (r, c) = (r as isize + dr, c as isize + dc);
+        match self.edges {
+            Edges::Wrap => Some((r.rem_euclid(rows) as usize, c.rem_euclid(cols) as usize)),
+            Edges::Fixed(_) if (0..rows).contains(&r) && (0..cols).contains(&c) => {
+                Some((r as usize, c as usize))
+            }
+            Edges::Fixed(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn life(alive: bool, around: &[bool]) -> bool {
+        matches!(
+            (alive, around.iter().filter(|&&n| n).count()),
+            (true, 2) | (_, 3)
+        )
+    }
+
+    fn show(cells: &CellMap<bool>) -> String {
+        let mut out = String::new();
+        for r in 0..cells.rows() {
+            out.extend((0..cells.cols()).map(|c| if cells[(r, c)] { '#' } else { '.' }));
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn neighbourhood_sizes() {
+        assert_eq!(Neighbourhood::moore(1).len(), 8);
+        assert_eq!(Neighbourhood::moore(2).len(), 24);
+        assert_eq!(Neighbourhood::von_neumann(1).len(), 4);
+        assert_eq!(Neighbourhood::von_neumann(2).len(), 12);
+        assert!(Neighbourhood::offsets([]).is_empty());
+    }
+
+    #[test]
+    fn blinker_oscillates_and_block_is_stable() {
+        let mut blinker = Automaton::new(
+            CellMap::parse(".....\n..#..\n..#..\n..#..\n.....\n", |ch| ch == '#'),
+            Neighbourhood::moore(1),
+            life,
+        );
+        let first = blinker.step();
+        assert_eq!(show(blinker.cells()), ".....\n.....\n.###.\n.....\n.....\n");
+        assert_eq!((first.index, first.changed, first.evaluated), (1, 4, 25));
+        blinker.step();
+        assert_eq!(show(blinker.cells()), ".....\n..#..\n..#..\n..#..\n.....\n");
+        assert_eq!(blinker.run(10).len(), 10);
+
+        let mut block = Automaton::new(
+            CellMap::parse("....\n.##.\n.##.\n....\n", |ch| ch == '#'),
+            Neighbourhood::moore(1),
+            life,
+        );
+        let generations = block.run(10);
+        assert_eq!(generations.len(), 1);
+        assert_eq!(generations[0].changed, 0);
+    }
+
+    #[test]
+    fn glider_wraps_back_to_the_start() {
+        let start = CellMap::parse(".#....\n..#...\n###...\n......\n......\n......\n", |ch| {
+            ch == '#'
+        });
+        let mut glider =
+            Automaton::new(start.clone(), Neighbourhood::moore(1), life).with_edges(Edges::Wrap);
+        // a glider moves one cell diagonally every 4 generations
+        for _ in 0..4 * 6 {
+            glider.step();
+        }
+        assert_eq!(glider.cells(), &start);
+        assert_eq!(glider.generation(), 24);
+    }
+
+    #[test]
+    fn settle_matches_run_for_one_way_rules() {
+        // cells with too few set neighbours clear, like the paper rolls
+        let erode = |set: bool, around: &[bool]| set && around.iter().filter(|&&n| n).count() >= 3;
+        let mut rng = Rng::new(46);
+        for _ in 0..20 {
+            let mut cells = CellMap::new(12, 17, false);
+            for r in 0..12 {
+                for c in 0..17 {
+                    cells[(r, c)] = rng.chance(60);
+                }
+            }
+
+            let mut synchronous = Automaton::new(cells.clone(), Neighbourhood::moore(1), erode);
+            let mut queued = Automaton::new(cells, Neighbourhood::moore(1), erode);
+            let run = synchronous.run(usize::MAX);
+            let settled = queued.settle();
+
+            assert_eq!(synchronous.cells(), queued.cells());
+            let total = |generations: &[Generation]| -> usize {
+                generations.iter().map(|g| g.changed).sum()
+            };
+            assert_eq!(total(&run), total(&settled));
+            assert_eq!(settled.last().map(|g| g.changed), Some(0));
+            assert_eq!(settled[0].evaluated, 12 * 17);
+            assert!(settled[1..].iter().all(|g| g.evaluated < 12 * 17));
+        }
+    }
+
+    #[test]
+    fn fixed_edges_read_as_the_given_state() {
+        // a cell turns on when any von neumann neighbour is on, the edge counts as on
+        let spread = |on: bool, around: &[bool]| on || around.contains(&true);
+        let mut grid = Automaton::new(
+            CellMap::new(3, 3, false),
+            Neighbourhood::von_neumann(1),
+            spread,
+        )
+        .with_edges(Edges::Fixed(true));
+        grid.step();
+        assert_eq!(show(grid.cells()), "###\n#.#\n###\n");
+        assert_eq!(grid.run(10).len(), 2);
+    }
+}
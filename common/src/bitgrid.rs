@@ -0,0 +1,393 @@
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+// boolean grid packed 64 cells to a word, row by row
+// cell (r, c) lives in bit c % 64 of word c / 64 of row r, and the unused bits
+// past the right edge of each row are always kept zero
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(64);
+        BitGrid {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    // one row per line, cells equal to `on` are set
+    pub fn parse(input: &str, on: char) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut grid = BitGrid::new(width, lines.len());
+
+        for (r, line) in lines.iter().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                if ch == on {
+                    grid.set(r, c, true);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        r < self.height
+            && c < self.width
+            && self.words[r * self.stride + c / 64] >> (c % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        assert!(
+            r < self.height && c < self.width,
+            "({}, {}) is outside the grid",
+            r,
+            c
+        );
+        let word = &mut self.words[r * self.stride + c / 64];
+        if value {
+            *word |= 1 << (c % 64);
+        } else {
+            *word &= !(1 << (c % 64));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    // set cells as (row, col), in row major order
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let (r, base) = (i / self.stride, (i % self.stride) * 64);
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some((r, base + bit))
+            })
+        })
+    }
+
+    pub fn and_not(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    // moves every cell `n` columns right, cells pushed off the edge are dropped
+    pub fn shift_east(&self, n: usize) -> BitGrid {
+        self.map_rows(|row, out| {
+            let (words, bits) = (n / 64, n % 64);
+            for i in (words..row.len()).rev() {
+                let src = i - words;
+                out[i] = row[src] << bits;
+                if bits > 0 && src > 0 {
+                    out[i] |= row[src - 1] >> (64 - bits);
+                }
+            }
+        })
+    }
+
+    // moves every cell `n` columns left, cells pushed off the edge are dropped
+    pub fn shift_west(&self, n: usize) -> BitGrid {
+        self.map_rows(|row, out| {
+            let (words, bits) = (n / 64, n % 64);
+            for (src, word) in (words..row.len()).zip(out.iter_mut()) {
+                *word = row[src] >> bits;
+                if bits > 0 && src + 1 < row.len() {
+                    *word |= row[src + 1] << (64 - bits);
+                }
+            }
+        })
+    }
+
+    // moves every cell `n` rows down
+    pub fn shift_south(&self, n: usize) -> BitGrid {
+        let mut out = BitGrid::new(self.width, self.height);
+        let moved = self.height.saturating_sub(n) * self.stride;
+        out.words[n.min(self.height) * self.stride..].copy_from_slice(&self.words[..moved]);
+        out
+    }
+
+    // moves every cell `n` rows up
+    pub fn shift_north(&self, n: usize) -> BitGrid {
+        let mut out = BitGrid::new(self.width, self.height);
+        let moved = self.height.saturating_sub(n) * self.stride;
+        out.words[..moved].copy_from_slice(&self.words[n.min(self.height) * self.stride..]);
+        out
+    }
+
+    // number of set cells among the 8 surrounding cells, for every cell at once
+    // returned as four bit planes, plane k holds bit k of the count
+    pub fn neighbour_counts(&self) -> [BitGrid; 4] {
+        let north = self.shift_north(1);
+        let south = self.shift_south(1);
+        let neighbours = [
+            north.shift_west(1),
+            north.clone(),
+            north.shift_east(1),
+            self.shift_west(1),
+            self.shift_east(1),
+            south.shift_west(1),
+            south.clone(),
+            south.shift_east(1),
+        ];
+
+        // bit sliced ripple carry adder, 64 cells per word operation
+        let mut planes: [Vec<u64>; 4] = std::array::from_fn(|_| vec![0; self.words.len()]);
+        for grid in &neighbours {
+            for (i, &word) in grid.words.iter().enumerate() {
+                let mut carry = word;
+                for plane in planes.iter_mut() {
+                    let next = plane[i] & carry;
+                    plane[i] ^= carry;
+                    carry = next;
+                }
+            }
+        }
+
+        planes.map(|words| BitGrid {
+            words,
+            ..self.empty_like()
+        })
+    }
+
+    // cells (set or not) with fewer than `n` set neighbours
+    pub fn fewer_neighbours_than(&self, n: u8) -> BitGrid {
+        let planes = self.neighbour_counts();
+        let mut result = self.empty_like();
+        for count in 0..n.min(9) {
+            let mut matches = !&self.empty_like();
+            for (k, plane) in planes.iter().enumerate() {
+                matches = if count >> k & 1 == 1 {
+                    &matches & plane
+                } else {
+                    matches.and_not(plane)
+                };
+            }
+            result = &result | &matches;
+        }
+        result
+    }
+
+    fn empty_like(&self) -> BitGrid {
+        BitGrid::new(self.width, self.height)
+    }
+
+    // clears the bits past the right edge so they never leak into counts or shifts
+    fn mask_padding(&mut self) {
+        let tail = self.width % 64;
+        if tail == 0 {
+            return;
+        }
+        let mask = (1u64 << tail) - 1;
+        for row in self.words.chunks_mut(self.stride) {
+            row[self.stride - 1] &= mask;
+        }
+    }
+
+    fn map_rows(&self, f: impl Fn(&[u64], &mut [u64])) -> BitGrid {
+        let mut out = self.empty_like();
+        if self.stride > 0 {
+            for (row, out_row) in self
+                .words
+                .chunks(self.stride)
+                .zip(out.words.chunks_mut(self.stride))
+            {
+                f(row, out_row);
+            }
+        }
+        out.mask_padding();
+        out
+    }
+
+    fn zip_words(&self, other: &BitGrid, f: impl Fn(u64, u64) -> u64) -> BitGrid {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "grid sizes differ"
+        );
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(&a, &b)| f(a, b))
+            .collect();
+        let mut out = BitGrid {
+            words,
+            ..self.empty_like()
+        };
+        out.mask_padding();
+        out
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut out = BitGrid {
+            words: self.words.iter().map(|w| !w).collect(),
+            ..self.empty_like()
+        };
+        out.mask_padding();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_grid(rng: &mut Rng, width: usize, height: usize) -> BitGrid {
+        let mut grid = BitGrid::new(width, height);
+        for r in 0..height {
+            for c in 0..width {
+                grid.set(r, c, rng.chance(45));
+            }
+        }
+        grid
+    }
+
+    fn count_at(planes: &[BitGrid; 4], r: usize, c: usize) -> usize {
+        (0..4).map(|k| (planes[k].get(r, c) as usize) << k).sum()
+    }
+
+    #[test]
+    fn neighbour_counts_match_naive() {
+        let mut rng = Rng::new(38);
+        // widths either side of the word boundary
+        for &(width, height) in &[(1, 1), (5, 7), (63, 4), (64, 3), (65, 5), (130, 9)] {
+            let grid = random_grid(&mut rng, width, height);
+            let planes = grid.neighbour_counts();
+            let fewer = grid.fewer_neighbours_than(4);
+
+            for r in 0..height {
+                for c in 0..width {
+                    let mut expected = 0;
+                    for dr in -1..=1_isize {
+                        for dc in -1..=1_isize {
+                            if (dr, dc) != (0, 0)
+                                && grid.get(r.wrapping_add_signed(dr), c.wrapping_add_signed(dc))
+                            {
+                                expected += 1;
+                            }
+                        }
+                    }
+                    assert_eq!(
+                        count_at(&planes, r, c),
+                        expected,
+                        "{}x{} at ({}, {})",
+                        width,
+                        height,
+                        r,
+                        c
+                    );
+                    assert_eq!(fewer.get(r, c), expected < 4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shifts_match_naive() {
+        let mut rng = Rng::new(380);
+        let grid = random_grid(&mut rng, 150, 6);
+        for n in [0, 1, 63, 64, 65, 149, 150] {
+            let (east, west) = (grid.shift_east(n), grid.shift_west(n));
+            for r in 0..6 {
+                for c in 0..150 {
+                    assert_eq!(
+                        east.get(r, c),
+                        c >= n && grid.get(r, c - n),
+                        "east {} ({}, {})",
+                        n,
+                        r,
+                        c
+                    );
+                    assert_eq!(
+                        west.get(r, c),
+                        grid.get(r, c + n),
+                        "west {} ({}, {})",
+                        n,
+                        r,
+                        c
+                    );
+                }
+            }
+        }
+        for n in [0, 1, 5, 6, 7] {
+            let (south, north) = (grid.shift_south(n), grid.shift_north(n));
+            for r in 0..6 {
+                for c in 0..150 {
+                    assert_eq!(south.get(r, c), r >= n && grid.get(r - n, c));
+                    assert_eq!(north.get(r, c), grid.get(r + n, c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = BitGrid::parse("#.#.\n##..\n", '#');
+        let b = BitGrid::parse("##..\n.#.#\n", '#');
+        assert_eq!((&a & &b).count_ones(), 2);
+        assert_eq!((&a | &b).count_ones(), 6);
+        assert_eq!((&a ^ &b).count_ones(), 4);
+        assert_eq!(
+            a.and_not(&b).iter_ones().collect::<Vec<_>>(),
+            vec![(0, 2), (1, 0)]
+        );
+        assert_eq!((!&a).count_ones(), 8 - a.count_ones());
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+use std::ops::{Index, IndexMut};
+
+// per-cell state for grids whose bounds are known up front, one flat Vec indexed
+// row * cols + col instead of a HashMap keyed by position
+// positions are (row, col) pairs of usize or any signed int, and anything outside
+// the grid (negative included) reads as missing, so neighbour lookups can go
+// straight to `get` without bounds checks of their own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellMap<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+// per-cell per-direction state, index with `map[pos][dir]`
+pub type DirMap<T> = CellMap<[T; 4]>;
+
+pub trait Cell: Copy + Debug {
+    // (row, col) if neither part is negative, bounds are checked by the map
+    fn cell(self) -> Option<(usize, usize)>;
+}
+
+impl Cell for (usize, usize) {
+    fn cell(self) -> Option<(usize, usize)> {
+        Some(self)
+    }
+}
+
+macro_rules! impl_signed_cell {
+    ($($t:ty),*) => {$(
+        impl Cell for ($t, $t) {
+            fn cell(self) -> Option<(usize, usize)> {
+                Some((usize::try_from(self.0).ok()?, usize::try_from(self.1).ok()?))
+            }
+        }
+    )*};
+}
+
+impl_signed_cell!(i32, i64, isize);
+
+impl<T: Clone> CellMap<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self {
+        CellMap {
+            rows,
+            cols,
+            cells: vec![fill; rows * cols],
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+}
+
+impl<T> CellMap<T> {
+    // one row per line, short lines are padded out as if they ended in spaces
+    pub fn parse(input: &str, cell: impl Fn(char) -> T) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let cols = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(lines.len() * cols);
+        for line in &lines {
+            let mut chars = line.chars();
+            cells.extend((0..cols).map(|_| cell(chars.next().unwrap_or(' '))));
+        }
+        CellMap {
+            rows: lines.len(),
+            cols,
+            cells,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn in_bounds(&self, pos: impl Cell) -> bool {
+        self.index_of(pos).is_some()
+    }
+
+    pub fn get(&self, pos: impl Cell) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: impl Cell) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.cells[i])
+    }
+
+    // every cell with its (row, col), in row major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, value)| ((i / self.cols, i % self.cols), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter()
+    }
+
+    fn index_of(&self, pos: impl Cell) -> Option<usize> {
+        let (r, c) = pos.cell()?;
+        (r < self.rows && c < self.cols).then(|| r * self.cols + c)
+    }
+
+    fn expect_index(&self, pos: impl Cell) -> usize {
+        self.index_of(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the {}x{} grid", pos, self.rows, self.cols))
+    }
+}
+
+// a set of cells, for visited flags and the like
+impl CellMap<bool> {
+    pub fn contains(&self, pos: impl Cell) -> bool {
+        self.get(pos) == Some(&true)
+    }
+
+    // true if the cell was not in the set yet, same as HashSet::insert
+    pub fn insert(&mut self, pos: impl Cell) -> bool {
+        let i = self.expect_index(pos);
+        !std::mem::replace(&mut self.cells[i], true)
+    }
+
+    pub fn count(&self) -> usize {
+        self.cells.iter().filter(|&&set| set).count()
+    }
+}
+
+impl<T, P: Cell> Index<P> for CellMap<T> {
+    type Output = T;
+
+    fn index(&self, pos: P) -> &T {
+        &self.cells[self.expect_index(pos)]
+    }
+}
+
+impl<T, P: Cell> IndexMut<P> for CellMap<T> {
+    fn index_mut(&mut self, pos: P) -> &mut T {
+        let i = self.expect_index(pos);
+        &mut self.cells[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_outside_read_as_missing() {
+        let mut map = CellMap::new(2, 3, 0);
+        map[(1usize, 2usize)] = 7;
+        map[(0i32, 1i32)] += 2;
+
+        assert_eq!(map.get((1i64, 2i64)), Some(&7));
+        assert_eq!(map.get((-1i32, 0i32)), None);
+        assert_eq!(map.get((0usize, 3usize)), None);
+        assert_eq!(map.get((2usize, 0usize)), None);
+        assert!(map.in_bounds((1isize, 0isize)));
+        assert_eq!(
+            map.iter()
+                .filter(|&(_, &v)| v > 0)
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_pads_short_lines() {
+        let map = CellMap::parse("#.\n#\n\n", |ch| ch == '#');
+        assert_eq!((map.rows(), map.cols()), (3, 2));
+        assert_eq!(
+            map.values().copied().collect::<Vec<_>>(),
+            vec![true, false, true, false, false, false]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "(3, 0) is outside the 2x3 grid")]
+    fn indexing_outside_panics() {
+        let map = CellMap::new(2, 3, 'x');
+        let _ = map[(3usize, 0usize)];
+    }
+
+    #[test]
+    fn sets_and_directions() {
+        let mut seen = CellMap::new(3, 3, false);
+        assert!(seen.insert((1i32, 1i32)));
+        assert!(!seen.insert((1usize, 1usize)));
+        assert!(seen.contains((1usize, 1usize)));
+        assert!(!seen.contains((-1i32, 1i32)));
+        assert_eq!(seen.count(), 1);
+
+        let mut dist: DirMap<usize> = CellMap::new(3, 3, [usize::MAX; 4]);
+        dist[(2usize, 0usize)][3] = 5;
+        assert_eq!(
+            dist[(2usize, 0usize)],
+            [usize::MAX, usize::MAX, usize::MAX, 5]
+        );
+        dist.fill([0; 4]);
+        assert!(dist.values().all(|dirs| dirs == &[0; 4]));
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// cycle detection for iterated step functions
+// the step returns None when the simulation stops (guard walks off the map etc),
+// in which case there is no cycle and every detector returns None
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    // index of the first state that is part of the cycle
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // maps any step index onto the equivalent index inside the first lap
+    // handy for "what does it look like after a billion steps"
+    pub fn reduce(&self, index: usize) -> usize {
+        if index < self.start {
+            index
+        } else {
+            self.start + (index - self.start) % self.length
+        }
+    }
+}
+
+// tortoise and hare, constant memory but steps about three times past the cycle
+pub fn floyd<T: Clone + PartialEq>(initial: T, step: impl Fn(&T) -> Option<T>) -> Option<Cycle> {
+    let mut tortoise = step(&initial)?;
+    let mut hare = step(&tortoise)?;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&step(&hare)?)?;
+    }
+
+    // the distance from the start to the cycle equals the distance
+    // from the meeting point to the cycle entrance
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise)?;
+    while tortoise != hare {
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+// teleporting tortoise, constant memory and fewer steps than floyd
+pub fn brent<T: Clone + PartialEq>(initial: T, step: impl Fn(&T) -> Option<T>) -> Option<Cycle> {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    // put the hare one cycle length ahead and walk both until they meet
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step(&hare)?;
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+// remembers every state, so it steps exactly once per state until the first repeat
+// needs memory for the whole prefix and cycle but the step can be stateful
+pub fn hashed<T: Clone + Hash + Eq>(
+    initial: T,
+    mut step: impl FnMut(&T) -> Option<T>,
+) -> Option<Cycle> {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    let mut index = 0;
+
+    loop {
+        if let Some(&first) = seen.get(&state) {
+            return Some(Cycle {
+                start: first,
+                length: index - first,
+            });
+        }
+        let next = step(&state)?;
+        seen.insert(state, index);
+        state = next;
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn detectors_agree_on_random_functions() {
+        let mut rng = Rng::new(36);
+        for _ in 0..500 {
+            // random map on 0..n always ends up in a cycle
+            let n = 1 + rng.below(60) as usize;
+            let next: Vec<usize> = (0..n).map(|_| rng.below(n as u64) as usize).collect();
+            let initial = rng.below(n as u64) as usize;
+            let step = |&x: &usize| Some(next[x]);
+
+            let expected = hashed(initial, step);
+            assert!(expected.is_some());
+            assert_eq!(floyd(initial, step), expected);
+            assert_eq!(brent(initial, step), expected);
+        }
+    }
+
+    #[test]
+    fn known_cycle() {
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 2
+        let step = |&x: &u32| Some(if x == 4 { 2 } else { x + 1 });
+        let cycle = Cycle {
+            start: 2,
+            length: 3,
+        };
+        assert_eq!(floyd(0, step), Some(cycle));
+        assert_eq!(brent(0, step), Some(cycle));
+        assert_eq!(hashed(0, step), Some(cycle));
+        assert_eq!(cycle.reduce(1), 1);
+        assert_eq!(cycle.reduce(1_000_000_000), 2 + (1_000_000_000 - 2) % 3);
+    }
+
+    #[test]
+    fn terminating_step_has_no_cycle() {
+        let step = |&x: &u32| (x < 10).then_some(x + 1);
+        assert_eq!(floyd(0, step), None);
+        assert_eq!(brent(0, step), None);
+        assert_eq!(hashed(0, step), None);
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::rng::Rng;
+
+// mutation fuzzer for the day parsers, run from ordinary #[test]s
+// there is no coverage feedback (that needs cargo fuzz and a nightly toolchain),
+// it just keeps mutating the seed inputs and reports the first input that panics,
+// shrunk down so it can be pasted straight into a regression test
+
+// how far past the longest seed an input may grow, keeps numbers in the seeds from
+// growing into overflow territory that the puzzle inputs never get near
+const MAX_GROWTH: usize = 8;
+
+// chars worth throwing at every parser on top of whatever the seeds contain
+const EXTRA_CHARS: [char; 6] = ['\n', ' ', '0', '9', '-', 'é'];
+
+pub fn check(seeds: &[&str], iterations: usize, target: impl Fn(&str)) {
+    if let Some(input) = find_crash(seeds, iterations, 0, &target) {
+        panic!(
+            "fuzzing found a crashing input, add it as a regression test: {:?}",
+            input
+        );
+    }
+}
+
+// the shrunk crashing input, if any
+pub fn find_crash(
+    seeds: &[&str],
+    iterations: usize,
+    seed: u64,
+    target: &impl Fn(&str),
+) -> Option<String> {
+    let seeds: Vec<Vec<char>> = seeds.iter().map(|s| s.chars().collect()).collect();
+    let alphabet: Vec<char> = seeds
+        .iter()
+        .flatten()
+        .copied()
+        .chain(EXTRA_CHARS)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let max_len = seeds.iter().map(Vec::len).max().unwrap_or(0) + MAX_GROWTH;
+
+    let mut rng = Rng::new(seed);
+    for seed in &seeds {
+        if crashes(target, seed) {
+            return Some(shrink(target, seed.clone()));
+        }
+    }
+
+    for _ in 0..iterations {
+        let mut input = seeds[rng.below(seeds.len() as u64) as usize].clone();
+        for _ in 0..=rng.below(4) {
+            mutate(&mut rng, &mut input, &seeds, &alphabet);
+        }
+        input.truncate(max_len);
+
+        if crashes(target, &input) {
+            return Some(shrink(target, input));
+        }
+    }
+    None
+}
+
+fn crashes(target: &impl Fn(&str), input: &[char]) -> bool {
+    let text: String = input.iter().collect();
+    panic::catch_unwind(AssertUnwindSafe(|| target(&text))).is_err()
+}
+
+fn mutate(rng: &mut Rng, input: &mut Vec<char>, seeds: &[Vec<char>], alphabet: &[char]) {
+    let pick = |rng: &mut Rng| alphabet[rng.below(alphabet.len() as u64) as usize];
+    let len = input.len() as u64;
+
+    match rng.below(7) {
+        0 if len > 0 => {
+            input.remove(rng.below(len) as usize);
+        }
+        1 => {
+            let ch = pick(rng);
+            input.insert(rng.below(len + 1) as usize, ch);
+        }
+        2 if len > 0 => {
+            let ch = pick(rng);
+            input[rng.below(len) as usize] = ch;
+        }
+        3 if len > 1 => input.swap(rng.below(len) as usize, rng.below(len) as usize),
+        4 => input.truncate(rng.below(len + 1) as usize),
+        5 if len > 0 => {
+            // repeat a short span, good at making ragged lines and repeated tokens
+            let start = rng.below(len) as usize;
+            let end = (start + 1 + rng.below(MAX_GROWTH as u64) as usize).min(input.len());
+            let span: Vec<char> = input[start..end].to_vec();
+            input.splice(end..end, span);
+        }
+        _ => {
+            // head of this input, tail of another seed
+            let other = &seeds[rng.below(seeds.len() as u64) as usize];
+            let cut = rng.below(len + 1) as usize;
+            let from = rng.below(other.len() as u64 + 1) as usize;
+            input.truncate(cut);
+            input.extend_from_slice(&other[from..]);
+        }
+    }
+}
+
+// drops chunks of shrinking size for as long as the input keeps crashing
+fn shrink(target: &impl Fn(&str), mut input: Vec<char>) -> String {
+    let mut chunk = input.len().div_ceil(2).max(1);
+    loop {
+        let mut start = 0;
+        while start < input.len() {
+            let end = (start + chunk).min(input.len());
+            let candidate: Vec<char> = input[..start]
+                .iter()
+                .chain(&input[end..])
+                .copied()
+                .collect();
+            if crashes(target, &candidate) {
+                input = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        if chunk == 1 {
+            break;
+        }
+        chunk /= 2;
+    }
+    input.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // splits "a,b" and indexes past the comma without checking, like the day 3 bug
+    fn fragile(input: &str) {
+        for line in input.lines() {
+            let comma = line.find(',').unwrap_or(line.len());
+            let _ = &line[comma + 1..];
+        }
+    }
+
+    #[test]
+    fn finds_and_shrinks_crash() {
+        let crash = find_crash(&["1,2\n3,4\n"], 1_000, 41, &fragile).expect("should crash");
+        // any line without a comma crashes and shrinking gets it down to one char
+        assert_eq!(crash.chars().count(), 1);
+        assert!(crashes(&fragile, &crash.chars().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn robust_target_passes() {
+        check(&["1,2\n3,4\n", "\n"], 1_000, |input| {
+            for line in input.lines() {
+                let _ = line.split_once(',').map(|(a, b)| (a.parse::<i32>(), b));
+            }
+        });
+    }
+}
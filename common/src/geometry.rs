@@ -0,0 +1,337 @@
+use std::ops::RangeInclusive;
+
+use crate::linalg;
+
+// integer plane geometry, points are (i64, i64) and work as (row, col) or (x, y) alike
+//   lattice points   the grid points on a segment, or on a whole line inside a Rect
+//   polygons         shoelace area, points on the boundary, and Pick's theorem for the
+//                    points inside (2A = 2I + B - 2 for a simple lattice polygon)
+//   outline          a region of grid cells as the polygons around it, one vertex per turn
+
+pub type Point = (i64, i64);
+
+// the smallest whole step from `a` towards `b`, (0, 0) when they're the same point
+pub fn lattice_step(a: Point, b: Point) -> Point {
+    let (d0, d1) = (b.0 - a.0, b.1 - a.1);
+    let g = linalg::gcd(d0.into(), d1.into()) as i64;
+    if g == 0 { (0, 0) } else { (d0 / g, d1 / g) }
+}
+
+// every lattice point on the segment from `a` to `b`, both ends included
+pub fn lattice_segment(a: Point, b: Point) -> impl Iterator<Item = Point> {
+    let step = lattice_step(a, b);
+    let n = linalg::gcd((b.0 - a.0).into(), (b.1 - a.1).into()) as i64;
+    (0..=n).map(move |t| (a.0 + t * step.0, a.1 + t * step.1))
+}
+
+// every lattice point on the whole line through `a` and `b` that is inside `bounds`,
+// in order along the direction from `a` to `b`
+pub fn lattice_line(a: Point, b: Point, bounds: &Rect) -> impl Iterator<Item = Point> {
+    assert!(a != b, "{:?} alone doesn't make a line", a);
+    let step = lattice_step(a, b);
+    let range = bounds.clip(a, step).into_iter().flatten();
+    range.map(move |t| (a.0 + t * step.0, a.1 + t * step.1))
+}
+
+// inclusive on both corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    pub fn new(min: Point, max: Point) -> Self {
+        Rect { min, max }
+    }
+
+    // the cells of a grid as (row, col)
+    pub fn grid(rows: usize, cols: usize) -> Self {
+        Rect::new((0, 0), (rows as i64 - 1, cols as i64 - 1))
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.0..=self.max.0).contains(&p.0) && (self.min.1..=self.max.1).contains(&p.1)
+    }
+
+    // the t for which origin + t * step is inside, None if the line misses
+    pub fn clip(&self, origin: Point, step: Point) -> Option<RangeInclusive<i64>> {
+        let (lo0, hi0) = axis_range(origin.0, step.0, self.min.0, self.max.0);
+        let (lo1, hi1) = axis_range(origin.1, step.1, self.min.1, self.max.1);
+        let (lo, hi) = (lo0.max(lo1), hi0.min(hi1));
+        (lo <= hi).then_some(lo..=hi)
+    }
+}
+
+// the t for which min <= p + t * d <= max
+fn axis_range(p: i64, d: i64, min: i64, max: i64) -> (i64, i64) {
+    match d {
+        0 if (min..=max).contains(&p) => (i64::MIN, i64::MAX),
+        0 => (1, 0),
+        // t >= ceil((min - p) / d) and t <= floor((max - p) / d)
+        d if d > 0 => (-((p - min).div_euclid(d)), (max - p).div_euclid(d)),
+        // same range for -t
+        d => {
+            let (lo, hi) = axis_range(p, -d, min, max);
+            (-hi, -lo)
+        }
+    }
+}
+
+// twice the signed area, positive when the vertices go anticlockwise with the first
+// coordinate as x, the last vertex joins back to the first
+pub fn double_signed_area(polygon: &[Point]) -> i64 {
+    let wrapped = polygon.iter().zip(polygon.iter().cycle().skip(1));
+    wrapped.map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum()
+}
+
+// twice the area, so it stays a whole number for any lattice polygon
+pub fn double_area(polygon: &[Point]) -> i64 {
+    double_signed_area(polygon).abs()
+}
+
+// lattice points on the edges, which for axis aligned edges is also the perimeter
+pub fn boundary_points(polygon: &[Point]) -> i64 {
+    let wrapped = polygon.iter().zip(polygon.iter().cycle().skip(1));
+    wrapped
+        .map(|(a, b)| linalg::gcd((b.0 - a.0).into(), (b.1 - a.1).into()) as i64)
+        .sum()
+}
+
+// lattice points strictly inside a simple polygon, by Pick's theorem
+pub fn interior_points(polygon: &[Point]) -> i64 {
+    (double_area(polygon) - boundary_points(polygon)) / 2 + 1
+}
+
+// the boundary of a region of unit cells, cell (r, c) covering the square between
+// corners (r, c) and (r + 1, c + 1), so the polygons are in corner coordinates
+// gives one polygon for the outside of each connected piece and one per hole, with only
+// the corners where the boundary turns. outsides go anticlockwise and holes clockwise (as
+// double_signed_area sees it), so summing the signed areas gives the cell count.
+// cells that only touch at a corner are kept apart, that corner shows up once per cell
+// `contains` has to agree with `cells` about what's in the region
+pub fn outline(cells: &[Point], contains: impl Fn(Point) -> bool) -> Vec<Vec<Point>> {
+    const DIRS: [Point; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+    let Some(&first) = cells.first() else {
+        return Vec::new();
+    };
+
+    // unit edges with the region on their left, kept as a mask of directions per corner
+    // over the corners of the bounding box
+    let (mut min, mut max) = (first, first);
+    for &(r, c) in cells {
+        min = (min.0.min(r), min.1.min(c));
+        max = (max.0.max(r), max.1.max(c));
+    }
+    let width = (max.1 - min.1 + 2) as usize;
+    let height = (max.0 - min.0 + 2) as usize;
+    let index = |(r, c): Point| (r - min.0) as usize * width + (c - min.1) as usize;
+    let mut edges = vec![0u8; width * height];
+
+    for &(r, c) in cells {
+        let sides = [
+            ((r - 1, c), (r, c + 1), 0),
+            ((r, c - 1), (r, c), 1),
+            ((r + 1, c), (r + 1, c), 2),
+            ((r, c + 1), (r + 1, c + 1), 3),
+        ];
+        for (neighbour, start, dir) in sides {
+            if !contains(neighbour) {
+                edges[index(start)] |= 1 << dir;
+            }
+        }
+    }
+
+    let mut polygons = Vec::new();
+    for start in 0..edges.len() {
+        while edges[start] != 0 {
+            let first_dir = edges[start].trailing_zeros() as usize;
+            edges[start] &= !(1 << first_dir);
+            let corner = (
+                min.0 + (start / width) as i64,
+                min.1 + (start % width) as i64,
+            );
+
+            let mut polygon = Vec::new();
+            let (mut at, mut dir) = (corner, first_dir);
+            loop {
+                let next = (at.0 + DIRS[dir].0, at.1 + DIRS[dir].1);
+                // turning left first hugs the region, which is what keeps corner touching
+                // cells apart. the first edge is gone from the mask, so closing the loop
+                // is checked by hand
+                let turn = [(dir + 1) % 4, dir, (dir + 3) % 4]
+                    .into_iter()
+                    .find(|&d| {
+                        (next, d) == (corner, first_dir) || edges[index(next)] & (1 << d) != 0
+                    })
+                    .expect("a boundary always carries on");
+                if turn != dir {
+                    polygon.push(next);
+                }
+                if (next, turn) == (corner, first_dir) {
+                    break;
+                }
+                edges[index(next)] &= !(1 << turn);
+                (at, dir) = (next, turn);
+            }
+            polygons.push(polygon);
+        }
+    }
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn lattice_points_on_segments_and_lines() {
+        assert_eq!(lattice_step((1, 1), (7, 10)), (2, 3));
+        assert_eq!(lattice_step((2, 2), (2, 2)), (0, 0));
+        assert_eq!(
+            lattice_segment((1, 1), (7, 10)).collect::<Vec<_>>(),
+            vec![(1, 1), (3, 4), (5, 7), (7, 10)]
+        );
+        assert_eq!(lattice_segment((3, 3), (3, 3)).count(), 1);
+
+        let bounds = Rect::grid(10, 10);
+        assert_eq!(
+            lattice_line((4, 4), (5, 6), &bounds).collect::<Vec<_>>(),
+            vec![(2, 0), (3, 2), (4, 4), (5, 6), (6, 8)]
+        );
+        // heading the other way lists the same points backwards
+        assert_eq!(
+            lattice_line((5, 6), (4, 4), &bounds).collect::<Vec<_>>(),
+            vec![(6, 8), (5, 6), (4, 4), (3, 2), (2, 0)]
+        );
+        assert_eq!(lattice_line((0, 0), (0, 3), &bounds).count(), 10);
+        assert_eq!(Rect::grid(3, 3).clip((10, 10), (1, 0)), None);
+    }
+
+    #[test]
+    fn clip_matches_walking_the_line() {
+        let mut rng = Rng::new(50);
+        for _ in 0..500 {
+            let bounds = Rect::new(
+                (rng.range(-5, 0), rng.range(-5, 0)),
+                (rng.range(0, 5), rng.range(0, 5)),
+            );
+            let origin = (rng.range(-8, 8), rng.range(-8, 8));
+            let step = (rng.range(-3, 3), rng.range(-3, 3));
+            if step == (0, 0) {
+                continue;
+            }
+            let walked: Vec<i64> = (-30..=30)
+                .filter(|t| bounds.contains((origin.0 + t * step.0, origin.1 + t * step.1)))
+                .collect();
+            let clipped: Vec<i64> = bounds.clip(origin, step).into_iter().flatten().collect();
+            assert_eq!(clipped, walked, "{:?} {:?} {:?}", bounds, origin, step);
+        }
+    }
+
+    #[test]
+    fn shoelace_and_pick() {
+        let triangle = [(0, 0), (4, 0), (0, 3)];
+        assert_eq!(double_signed_area(&triangle), 12);
+        assert_eq!(double_signed_area(&[(0, 0), (0, 3), (4, 0)]), -12);
+        assert_eq!(boundary_points(&triangle), 4 + 3 + 1);
+
+        // count the inside points of some random triangles by hand
+        let mut rng = Rng::new(500);
+        for _ in 0..100 {
+            let polygon: Vec<Point> = (0..3)
+                .map(|_| (rng.range(0, 12), rng.range(0, 12)))
+                .collect();
+            if double_area(&polygon) == 0 {
+                continue;
+            }
+            let side = |a: Point, b: Point, p: Point| {
+                (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+            };
+            let sign = double_signed_area(&polygon).signum();
+            let inside = (0..=12)
+                .flat_map(|x| (0..=12).map(move |y| (x, y)))
+                .filter(|&p| (0..3).all(|i| side(polygon[i], polygon[(i + 1) % 3], p) * sign > 0))
+                .count() as i64;
+            assert_eq!(interior_points(&polygon), inside, "{:?}", polygon);
+        }
+    }
+
+    fn cells_of(picture: &str) -> Vec<Point> {
+        picture
+            .lines()
+            .enumerate()
+            .flat_map(|(r, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|&(_, ch)| ch == '#')
+                    .map(move |(c, _)| (r as i64, c as i64))
+            })
+            .collect()
+    }
+
+    fn outline_of(cells: &[Point]) -> Vec<Vec<Point>> {
+        let set: HashSet<Point> = cells.iter().copied().collect();
+        outline(cells, |p| set.contains(&p))
+    }
+
+    #[test]
+    fn outlines_of_shapes() {
+        let square = outline_of(&[(0, 0)]);
+        assert_eq!(square.len(), 1);
+        assert_eq!(square[0].len(), 4);
+        assert_eq!(double_signed_area(&square[0]), 2);
+
+        // a ring has an outside and a hole going the other way
+        let ring = outline_of(&cells_of("###\n#.#\n###\n"));
+        let mut areas: Vec<i64> = ring.iter().map(|p| double_signed_area(p)).collect();
+        areas.sort();
+        assert_eq!(areas, vec![-2, 18]);
+
+        // corner touching cells stay two squares
+        let pinch = outline_of(&cells_of("#.\n.#\n"));
+        assert_eq!(pinch.len(), 2);
+        assert!(pinch.iter().all(|p| p.len() == 4));
+    }
+
+    #[test]
+    fn outlines_match_counting_cells() {
+        let mut rng = Rng::new(5000);
+        for _ in 0..100 {
+            let cells: Vec<Point> = (0..8)
+                .flat_map(|r| (0..8).map(move |c| (r, c)))
+                .filter(|_| rng.chance(55))
+                .collect();
+            let set: HashSet<Point> = cells.iter().copied().collect();
+            let polygons = outline(&cells, |p| set.contains(&p));
+
+            let area: i64 = polygons.iter().map(|p| double_signed_area(p)).sum();
+            assert_eq!(area, 2 * cells.len() as i64);
+
+            let exposed = cells
+                .iter()
+                .flat_map(|&(r, c)| [(r - 1, c), (r + 1, c), (r, c - 1), (r, c + 1)])
+                .filter(|p| !set.contains(p))
+                .count() as i64;
+            assert_eq!(
+                polygons.iter().map(|p| boundary_points(p)).sum::<i64>(),
+                exposed
+            );
+
+            // every vertex is a real turn
+            for polygon in &polygons {
+                for i in 0..polygon.len() {
+                    let (a, b, c) = (
+                        polygon[i],
+                        polygon[(i + 1) % polygon.len()],
+                        polygon[(i + 2) % polygon.len()],
+                    );
+                    let (d1, d2) = (lattice_step(a, b), lattice_step(b, c));
+                    assert!(d1 != d2, "{:?} has a straight vertex at {:?}", polygon, b);
+                }
+            }
+        }
+    }
+}
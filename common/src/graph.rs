@@ -0,0 +1,335 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// directed graph over nodes 0..n stored as adjacency lists
+// puzzles with named nodes can go through `from_named_edges` to get the indices
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    adj: Vec<Vec<usize>>,
+}
+
+// returned by `topological_sort` when the graph isnt a DAG
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    // one cycle in edge order, the last node points back to the first
+    pub cycle: Vec<usize>,
+}
+
+impl Graph {
+    pub fn new(nodes: usize) -> Self {
+        Graph {
+            adj: vec![Vec::new(); nodes],
+        }
+    }
+
+    pub fn from_edges(nodes: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut graph = Graph::new(nodes);
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    // numbers the names in order of first appearance, returns the graph and the names by index
+    pub fn from_named_edges<'a>(
+        edges: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> (Self, Vec<&'a str>) {
+        let mut index = HashMap::new();
+        let mut names = Vec::new();
+        let mut graph = Graph::default();
+
+        for (from, to) in edges {
+            let [from, to] = [from, to].map(|name| {
+                *index.entry(name).or_insert_with(|| {
+                    names.push(name);
+                    graph.adj.push(Vec::new());
+                    names.len() - 1
+                })
+            });
+            graph.add_edge(from, to);
+        }
+
+        (graph, names)
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.adj[from].push(to);
+    }
+
+    pub fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adj.is_empty()
+    }
+
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.adj[node]
+    }
+
+    // kahn's algorithm, starting nodes are taken in index order so the result is deterministic
+    pub fn topological_sort(&self) -> Result<Vec<usize>, CycleError> {
+        let mut in_degree = vec![0; self.len()];
+        for targets in &self.adj {
+            for &to in targets {
+                in_degree[to] += 1;
+            }
+        }
+
+        let mut queue: VecDeque<usize> = (0..self.len()).filter(|&n| in_degree[n] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &to in &self.adj[node] {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(CycleError {
+                cycle: self.find_cycle(&in_degree),
+            })
+        }
+    }
+
+    // every node kahn couldnt place still has an incoming edge from another
+    // unplaced node, so walking those edges backwards must loop
+    fn find_cycle(&self, in_degree: &[usize]) -> Vec<usize> {
+        let mut predecessor = vec![None; self.len()];
+        for (from, targets) in self.adj.iter().enumerate() {
+            for &to in targets {
+                if in_degree[from] > 0 && in_degree[to] > 0 {
+                    predecessor[to] = Some(from);
+                }
+            }
+        }
+
+        let mut node = (0..self.len()).find(|&n| in_degree[n] > 0).unwrap();
+        let mut seen = HashSet::new();
+        while seen.insert(node) {
+            node = predecessor[node].unwrap();
+        }
+
+        // node is on the cycle now, walk it once more to collect it
+        let mut cycle = vec![node];
+        let mut current = predecessor[node].unwrap();
+        while current != node {
+            cycle.push(current);
+            current = predecessor[current].unwrap();
+        }
+        cycle.reverse();
+        cycle
+    }
+
+    // tarjan's algorithm, written iteratively so long chains dont blow the stack
+    // components come out in reverse topological order of the condensed graph
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let n = self.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low_link = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+
+            // (node, position in its adjacency list)
+            let mut work = vec![(root, 0)];
+            while let Some(&mut (node, ref mut edge)) = work.last_mut() {
+                if *edge == 0 {
+                    index[node] = next_index;
+                    low_link[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+
+                if let Some(&to) = self.adj[node].get(*edge) {
+                    *edge += 1;
+                    if index[to] == UNVISITED {
+                        work.push((to, 0));
+                    } else if on_stack[to] {
+                        low_link[node] = low_link[node].min(index[to]);
+                    }
+                    continue;
+                }
+
+                // all edges done, close the node off
+                work.pop();
+                if let Some(&(parent, _)) = work.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    // bron-kerbosch with pivoting, edges are treated as undirected
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let mut undirected = vec![HashSet::new(); self.len()];
+        for (from, targets) in self.adj.iter().enumerate() {
+            for &to in targets {
+                if from != to {
+                    undirected[from].insert(to);
+                    undirected[to].insert(from);
+                }
+            }
+        }
+
+        let mut cliques = Vec::new();
+        bron_kerbosch(
+            &undirected,
+            &mut Vec::new(),
+            (0..self.len()).collect(),
+            HashSet::new(),
+            &mut cliques,
+        );
+        cliques
+    }
+
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(|clique| clique.len())
+            .unwrap_or_default()
+    }
+}
+
+fn bron_kerbosch(
+    adj: &[HashSet<usize>],
+    clique: &mut Vec<usize>,
+    mut candidates: HashSet<usize>,
+    mut excluded: HashSet<usize>,
+    cliques: &mut Vec<Vec<usize>>,
+) {
+    if candidates.is_empty() && excluded.is_empty() {
+        let mut found = clique.clone();
+        found.sort_unstable();
+        cliques.push(found);
+        return;
+    }
+
+    // pivot on the node with the most candidate neighbours, its neighbours can be skipped
+    let pivot = candidates
+        .iter()
+        .chain(&excluded)
+        .copied()
+        .max_by_key(|&p| adj[p].intersection(&candidates).count())
+        .unwrap();
+    let to_visit: Vec<usize> = candidates.difference(&adj[pivot]).copied().collect();
+
+    for node in to_visit {
+        clique.push(node);
+        bron_kerbosch(
+            adj,
+            clique,
+            candidates.intersection(&adj[node]).copied().collect(),
+            excluded.intersection(&adj[node]).copied().collect(),
+            cliques,
+        );
+        clique.pop();
+
+        candidates.remove(&node);
+        excluded.insert(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topological_order() {
+        let graph = Graph::from_edges(5, [(3, 1), (1, 0), (3, 2), (2, 0), (4, 3)]);
+        assert_eq!(graph.topological_sort(), Ok(vec![4, 3, 1, 2, 0]));
+    }
+
+    #[test]
+    fn topological_sort_reports_cycle() {
+        // 0 -> 1 -> 2 -> 3 -> 1, and 3 -> 4 hangs off the cycle
+        let graph = Graph::from_edges(5, [(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)]);
+        let mut cycle = graph.topological_sort().unwrap_err().cycle;
+        // rotate so the check doesnt depend on where the walk entered the cycle
+        let start = cycle.iter().position(|&n| n == 1).unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(cycle, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn strongly_connected() {
+        let graph = Graph::from_edges(
+            8,
+            [
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (6, 5),
+                (6, 7),
+                (7, 6),
+            ],
+        );
+        let mut components: Vec<Vec<usize>> = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|mut c| {
+                c.sort_unstable();
+                c
+            })
+            .collect();
+        components.sort();
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7]]);
+    }
+
+    #[test]
+    fn cliques_from_named_edges() {
+        // the example network from 2024 day 23, trimmed down
+        let edges = [
+            ("ka", "co"),
+            ("ta", "co"),
+            ("de", "co"),
+            ("ta", "ka"),
+            ("de", "ta"),
+            ("ka", "de"),
+            ("yn", "aq"),
+        ];
+        let (graph, names) = Graph::from_named_edges(edges);
+        let mut largest: Vec<&str> = graph
+            .maximum_clique()
+            .into_iter()
+            .map(|n| names[n])
+            .collect();
+        largest.sort_unstable();
+        assert_eq!(largest, vec!["co", "de", "ka", "ta"]);
+        assert_eq!(graph.maximal_cliques().len(), 2);
+    }
+}
@@ -0,0 +1,176 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+// reads a puzzle input and cleans it up so every day sees the same shape of text
+// if `path` doesnt exist but `path.gz` does, the compressed copy is used instead
+// AOC_INPUT names a file to read in place of `path`, that's how `aoc serve` hands a day its input
+pub fn load(path: &str) -> String {
+    let path = input_path(path, env::var("AOC_INPUT").ok());
+    try_load(&path).unwrap_or_else(|e| panic!("Failed to read input {}: {}", path, e))
+}
+
+fn input_path(path: &str, replacement: Option<String>) -> String {
+    replacement
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| path.to_string())
+}
+
+// `load` for callers that can live without the input, like the preview in `aoc tui`
+pub fn try_load(path: &str) -> std::io::Result<String> {
+    read_raw(path).map(|raw| normalize(&raw))
+}
+
+// reads the puzzle input for a day
+// `cargo run -- --generate <size> [seed]` swaps in a synthetic input instead,
+// which is handy for scaling and performance runs
+pub fn load_or_generate(path: &str, generate: fn(usize, u64) -> String) -> String {
+    let args: Vec<String> = env::args().skip(1).collect();
+    load_or_generate_from(&args, path, generate)
+}
+
+fn load_or_generate_from(
+    args: &[String],
+    path: &str,
+    generate: fn(usize, u64) -> String,
+) -> String {
+    match args.first().map(String::as_str) {
+        Some("--generate") => {
+            let size = args
+                .get(1)
+                .and_then(|s| s.parse().ok())
+                .expect("Usage: --generate <size> [seed]");
+            let seed = args
+                .get(2)
+                .map_or(0, |s| s.parse().expect("Seed must be a number"));
+            generate(size, seed)
+        }
+        _ => load(path),
+    }
+}
+
+// strips a leading BOM, turns \r\n and lone \r into \n and
+// leaves exactly one newline at the end (none for empty input)
+pub fn normalize(raw: &str) -> String {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text = text.trim_end_matches('\n');
+
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+fn read_raw(path: &str) -> std::io::Result<String> {
+    let gz_path = format!("{}.gz", path);
+
+    if path.ends_with(".gz") {
+        read_gzip(Path::new(path))
+    } else if !Path::new(path).exists() && Path::new(&gz_path).exists() {
+        read_gzip(Path::new(&gz_path))
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn read_gzip(path: &Path) -> std::io::Result<String> {
+    let file = fs::File::open(path)?;
+    let mut text = String::new();
+    MultiGzDecoder::new(file).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn normalizes_windows_input() {
+        assert_eq!(
+            normalize("\u{feff}47|53\r\n\r\n75,47\r\n\r\n"),
+            "47|53\n\n75,47\n"
+        );
+    }
+
+    #[test]
+    fn normalizes_trailing_newlines() {
+        assert_eq!(normalize("..#\n.#."), "..#\n.#.\n");
+        assert_eq!(normalize("..#\n.#.\n\n\n"), "..#\n.#.\n");
+        assert_eq!(normalize("a\rb\r"), "a\nb\n");
+        assert_eq!(normalize("\n\n"), "");
+    }
+
+    #[test]
+    fn reads_gzip_next_to_missing_input() {
+        let dir = env::temp_dir().join(format!("common-input-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("input.txt");
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"1,2\r\n3,4").unwrap();
+        fs::write(dir.join("input.txt.gz"), encoder.finish().unwrap()).unwrap();
+
+        assert_eq!(load(path.to_str().unwrap()), "1,2\n3,4\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn aoc_input_replaces_the_path() {
+        assert_eq!(input_path("src/input.txt", None), "src/input.txt");
+        assert_eq!(
+            input_path("src/input.txt", Some("/tmp/other.txt".to_string())),
+            "/tmp/other.txt"
+        );
+        assert_eq!(
+            input_path("src/input.txt", Some(String::new())),
+            "src/input.txt"
+        );
+    }
+
+    fn fake(size: usize, seed: u64) -> String {
+        format!("{} {}\n", size, seed)
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn generate_flag_swaps_in_a_generated_input() {
+        assert_eq!(
+            load_or_generate_from(&args("--generate 50 7"), "missing.txt", fake),
+            "50 7\n"
+        );
+        // the seed is optional
+        assert_eq!(
+            load_or_generate_from(&args("--generate 3"), "missing.txt", fake),
+            "3 0\n"
+        );
+
+        let dir = env::temp_dir().join(format!("common-generate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("input.txt");
+        fs::write(&path, "real\r\n").unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(load_or_generate_from(&[], path, fake), "real\n");
+        assert_eq!(
+            load_or_generate_from(&args("--render out.png"), path, fake),
+            "real\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Usage: --generate <size> [seed]")]
+    fn generate_flag_needs_a_size() {
+        load_or_generate_from(&args("--generate lots"), "missing.txt", fake);
+    }
+}
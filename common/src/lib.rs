@@ -0,0 +1,20 @@
+// shared helpers for the day crates
+// each day pulls this in with a path dependency so the days stay standalone
+
+pub mod alloc;
+pub mod automaton;
+pub mod bitgrid;
+pub mod cells;
+pub mod cycle;
+pub mod fuzz;
+pub mod geometry;
+pub mod graph;
+pub mod input;
+pub mod linalg;
+pub mod matching;
+pub mod num;
+pub mod par;
+pub mod render;
+pub mod rng;
+pub mod sim;
+pub mod spatial;
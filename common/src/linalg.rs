@@ -0,0 +1,539 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// exact linear systems A x = b, no floats anywhere
+//   solve          over the rationals by gaussian elimination, the whole solution set
+//                  as one particular solution plus a direction per free variable
+//   solve_integer  integer solutions only, via the hermite normal form, as one
+//                  particular solution plus a lattice basis
+// everything is i128 underneath, an overflow panics in debug like the rest of the maths
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    // always in lowest terms with a positive denominator
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "{}/0 is not a number", num);
+        let g = gcd(num, den) * den.signum();
+        Rational {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub fn num(self) -> i128 {
+        self.num
+    }
+
+    pub fn den(self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.num == 0
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational { num: n, den: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::from(n as i128)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Rational) -> Rational {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            den => write!(f, "{}/{}", self.num, den),
+        }
+    }
+}
+
+// always >= 0, gcd(0, 0) is 0
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// (g, s, t) with s*a + t*b = g = gcd(a, b)
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut s0, mut s1) = (1, 0);
+    let (mut t0, mut t1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 < 0 {
+        (-r0, -s0, -t0)
+    } else {
+        (r0, s0, t0)
+    }
+}
+
+// every x = particular + sum of t_i * free[i], for any t_i (rational or integer,
+// depending on which solver it came from)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solutions<T> {
+    pub particular: Vec<T>,
+    pub free: Vec<Vec<T>>,
+}
+
+impl<T> Solutions<T> {
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+// all rational x with A x = b, None when there are none
+// `a` is one row per equation, all rows as long as the number of unknowns
+pub fn solve(a: &[Vec<Rational>], b: &[Rational]) -> Option<Solutions<Rational>> {
+    assert_eq!(a.len(), b.len(), "one right hand side per equation");
+    let cols = a.first().map_or(0, Vec::len);
+
+    // augmented matrix, brought to reduced row echelon form
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| {
+            assert_eq!(row.len(), cols, "ragged matrix");
+            row.iter().copied().chain([rhs]).collect()
+        })
+        .collect();
+
+    let mut pivots = Vec::new();
+    for col in 0..cols {
+        let row = pivots.len();
+        let Some(found) = (row..m.len()).find(|&r| !m[r][col].is_zero()) else {
+            continue;
+        };
+        m.swap(row, found);
+
+        let pivot = m[row][col];
+        for x in &mut m[row] {
+            *x = *x / pivot;
+        }
+        let pivot_row = m[row].clone();
+        for (r, other) in m.iter_mut().enumerate() {
+            let factor = other[col];
+            if r != row && !factor.is_zero() {
+                for (x, &p) in other[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *x = *x - factor * p;
+                }
+            }
+        }
+        pivots.push(col);
+    }
+
+    // a leftover row reading 0 = something
+    if m[pivots.len()..].iter().any(|row| !row[cols].is_zero()) {
+        return None;
+    }
+
+    let mut particular = vec![Rational::ZERO; cols];
+    for (row, &col) in pivots.iter().enumerate() {
+        particular[col] = m[row][cols];
+    }
+
+    // setting one free variable to 1 moves every pivot variable against its column
+    let free = (0..cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|col| {
+            let mut direction = vec![Rational::ZERO; cols];
+            direction[col] = Rational::ONE;
+            for (row, &pivot) in pivots.iter().enumerate() {
+                direction[pivot] = -m[row][col];
+            }
+            direction
+        })
+        .collect();
+
+    Some(Solutions { particular, free })
+}
+
+// column style hermite normal form: (H, U) with H = A U, U unimodular (so an integer
+// matrix with an integer inverse), and H lower triangular in echelon form with positive
+// pivots and the entries left of each pivot reduced to 0..pivot
+pub fn hermite_normal_form(a: &[Vec<i128>]) -> (Vec<Vec<i128>>, Vec<Vec<i128>>) {
+    let cols = a.first().map_or(0, Vec::len);
+    let mut h = a.to_vec();
+    let mut u: Vec<Vec<i128>> = (0..cols)
+        .map(|r| (0..cols).map(|c| (r == c) as i128).collect())
+        .collect();
+
+    let mut pivot = 0;
+    for row in 0..h.len() {
+        if pivot == cols {
+            break;
+        }
+
+        // fold every column right of the pivot into it with gcd steps, leaving zeros behind
+        for col in pivot + 1..cols {
+            let (x, y) = (h[row][pivot], h[row][col]);
+            if y == 0 {
+                continue;
+            }
+            let (g, s, t) = extended_gcd(x, y);
+            let (p, q) = (x / g, y / g);
+            // [s -q; t p] has determinant s*p + t*q = 1
+            combine_columns(&mut h, pivot, col, (s, t, -q, p));
+            combine_columns(&mut u, pivot, col, (s, t, -q, p));
+        }
+
+        let value = h[row][pivot];
+        if value == 0 {
+            continue;
+        }
+        if value < 0 {
+            negate_column(&mut h, pivot);
+            negate_column(&mut u, pivot);
+        }
+        let value = h[row][pivot];
+        for col in 0..pivot {
+            let q = h[row][col].div_euclid(value);
+            if q != 0 {
+                combine_columns(&mut h, col, pivot, (1, -q, 0, 1));
+                combine_columns(&mut u, col, pivot, (1, -q, 0, 1));
+            }
+        }
+        pivot += 1;
+    }
+    (h, u)
+}
+
+// all integer x with A x = b, None when there are none
+// the free directions are a basis of the integer kernel, any integer combination of
+// them can be added to the particular solution
+pub fn solve_integer(a: &[Vec<i128>], b: &[i128]) -> Option<Solutions<i128>> {
+    assert_eq!(a.len(), b.len(), "one right hand side per equation");
+    let cols = a.first().map_or(0, Vec::len);
+    let (h, u) = hermite_normal_form(a);
+
+    // A x = b is H y = b with x = U y, and H is triangular so y falls out row by row
+    let mut y = Vec::with_capacity(cols);
+    for (row, &rhs) in h.iter().zip(b) {
+        let known: i128 = row.iter().zip(&y).map(|(h, y)| h * y).sum();
+        let rest = rhs - known;
+        match row.get(y.len()) {
+            Some(&pivot) if pivot != 0 => {
+                if rest % pivot != 0 {
+                    return None;
+                }
+                y.push(rest / pivot);
+            }
+            _ if rest != 0 => return None,
+            _ => {}
+        }
+    }
+
+    let rank = y.len();
+    let particular = (0..cols)
+        .map(|r| u[r][..rank].iter().zip(&y).map(|(u, y)| u * y).sum())
+        .collect();
+    let free = (rank..cols)
+        .map(|c| (0..cols).map(|r| u[r][c]).collect())
+        .collect();
+    Some(Solutions { particular, free })
+}
+
+// col i, col j = a*col i + b*col j, c*col i + d*col j
+fn combine_columns(
+    m: &mut [Vec<i128>],
+    i: usize,
+    j: usize,
+    (a, b, c, d): (i128, i128, i128, i128),
+) {
+    for row in m {
+        let (x, y) = (row[i], row[j]);
+        row[i] = a * x + b * y;
+        row[j] = c * x + d * y;
+    }
+}
+
+fn negate_column(m: &mut [Vec<i128>], i: usize) {
+    for row in m {
+        row[i] = -row[i];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn ints(rows: &[&[i128]]) -> Vec<Vec<i128>> {
+        rows.iter().map(|row| row.to_vec()).collect()
+    }
+
+    fn rationals(rows: &[Vec<i128>]) -> Vec<Vec<Rational>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| Rational::from(x)).collect())
+            .collect()
+    }
+
+    fn apply<T>(a: &[Vec<T>], x: &[T]) -> Vec<T>
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + From<i64>,
+    {
+        a.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x)
+                    .fold(T::from(0), |sum, (&a, &x)| sum + a * x)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rationals_stay_in_lowest_terms() {
+        assert_eq!(Rational::new(6, -4), Rational::new(-3, 2));
+        assert_eq!(Rational::new(-3, 2).to_string(), "-3/2");
+        assert_eq!(
+            Rational::new(1, 3) + Rational::new(1, 6),
+            Rational::new(1, 2)
+        );
+        assert_eq!(
+            (Rational::new(2, 3) / Rational::new(4, 9)).to_integer(),
+            None
+        );
+        assert_eq!(
+            (Rational::new(3, 4) * Rational::from(4i128)).to_integer(),
+            Some(3)
+        );
+        assert!(Rational::new(-1, 2) < Rational::new(-1, 3));
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+    }
+
+    #[test]
+    fn unique_free_and_inconsistent_systems() {
+        // x + y + z = 6, 2y + 5z = -4, 2x + 5y - z = 27
+        let a = rationals(&ints(&[&[1, 1, 1], &[0, 2, 5], &[2, 5, -1]]));
+        let b: Vec<Rational> = [6i128, -4, 27].map(Rational::from).to_vec();
+        let unique = solve(&a, &b).unwrap();
+        assert!(unique.is_unique());
+        assert_eq!(
+            unique.particular,
+            [5i128, 3, -2].map(Rational::from).to_vec()
+        );
+
+        // the second row is twice the first, so one free variable
+        let a = rationals(&ints(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]));
+        let b: Vec<Rational> = [4i128, 8, 1].map(Rational::from).to_vec();
+        let line = solve(&a, &b).unwrap();
+        assert_eq!(line.free.len(), 1);
+        for t in [-3, 0, 7] {
+            let x: Vec<Rational> = line
+                .particular
+                .iter()
+                .zip(&line.free[0])
+                .map(|(&p, &d)| p + Rational::from(t as i128) * d)
+                .collect();
+            assert_eq!(apply(&a, &x), b);
+        }
+
+        let b: Vec<Rational> = [4i128, 9, 1].map(Rational::from).to_vec();
+        assert_eq!(solve(&a, &b), None);
+    }
+
+    #[test]
+    fn hermite_form_is_a_times_unimodular() {
+        let mut rng = Rng::new(49);
+        for _ in 0..100 {
+            let rows = rng.range(1, 4) as usize;
+            let cols = rng.range(1, 4) as usize;
+            let a: Vec<Vec<i128>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.range(-9, 9) as i128).collect())
+                .collect();
+            let (h, u) = hermite_normal_form(&a);
+
+            for (r, row) in h.iter().enumerate() {
+                let expected: Vec<i128> = (0..cols)
+                    .map(|c| (0..cols).map(|k| a[r][k] * u[k][c]).sum())
+                    .collect();
+                assert_eq!(row, &expected, "{:?}", a);
+            }
+            // unimodular means the inverse is all integers, column by column
+            for c in 0..cols {
+                let unit: Vec<Rational> = (0..cols)
+                    .map(|r| Rational::from((r == c) as i128))
+                    .collect();
+                let column = solve(&rationals(&u), &unit).expect("u is invertible");
+                assert!(column.is_unique());
+                assert!(
+                    column.particular.iter().all(|x| x.to_integer().is_some()),
+                    "{:?}",
+                    u
+                );
+            }
+            // and h really is in echelon form
+            let mut pivot = 0;
+            for row in &h {
+                assert!(
+                    row[pivot.min(cols)..].iter().skip(1).all(|&x| x == 0),
+                    "{:?}",
+                    h
+                );
+                if pivot < cols && row[pivot] != 0 {
+                    assert!(row[pivot] > 0);
+                    assert!(
+                        row[..pivot].iter().all(|&x| (0..row[pivot]).contains(&x)),
+                        "{:?}",
+                        h
+                    );
+                    pivot += 1;
+                }
+            }
+        }
+    }
+
+    // every integer solution in a box, found by trying them all
+    fn brute_force(a: &[Vec<i128>], b: &[i128]) -> Vec<Vec<i128>> {
+        let cols = a[0].len();
+        let mut found = Vec::new();
+        let mut x = vec![-6; cols];
+        loop {
+            if apply(a, &x) == b {
+                found.push(x.clone());
+            }
+            let Some(i) = x.iter().position(|&v| v < 6) else {
+                return found;
+            };
+            x[i] += 1;
+            x[..i].fill(-6);
+        }
+    }
+
+    #[test]
+    fn integer_solutions_match_brute_force() {
+        let mut rng = Rng::new(490);
+        for _ in 0..200 {
+            let rows = rng.range(1, 3) as usize;
+            let cols = rng.range(1, 3) as usize;
+            let a: Vec<Vec<i128>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.range(-4, 4) as i128).collect())
+                .collect();
+            // aim at a solution half the time, otherwise anything
+            let b = if rng.chance(50) {
+                let x: Vec<i128> = (0..cols).map(|_| rng.range(-6, 6) as i128).collect();
+                apply(&a, &x)
+            } else {
+                (0..rows).map(|_| rng.range(-10, 10) as i128).collect()
+            };
+
+            let expected = brute_force(&a, &b);
+            let Some(solutions) = solve_integer(&a, &b) else {
+                assert!(
+                    expected.is_empty(),
+                    "{:?} x = {:?} has {:?}",
+                    a,
+                    b,
+                    expected
+                );
+                continue;
+            };
+            assert_eq!(apply(&a, &solutions.particular), b);
+            for direction in &solutions.free {
+                assert!(apply(&a, direction).iter().all(|&v| v == 0));
+            }
+
+            // each one found by brute force is the particular solution plus an integer
+            // combination of the free directions
+            let basis: Vec<Vec<Rational>> = (0..cols)
+                .map(|r| {
+                    solutions
+                        .free
+                        .iter()
+                        .map(|d| Rational::from(d[r]))
+                        .collect()
+                })
+                .collect();
+            for x in expected {
+                if solutions.free.is_empty() {
+                    assert_eq!(x, solutions.particular);
+                    continue;
+                }
+                let offset: Vec<Rational> = x
+                    .iter()
+                    .zip(&solutions.particular)
+                    .map(|(&x, &p)| Rational::from(x - p))
+                    .collect();
+                let t = solve(&basis, &offset).expect("in the span of the kernel");
+                assert!(t.is_unique());
+                assert!(t.particular.iter().all(|t| t.to_integer().is_some()));
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+
+// multi-pattern string matching over any symbol type, u8 for bytes (`s.bytes()`)
+// and char for text that isnt ascii (`s.chars()`), positions count symbols
+//   Trie         which patterns a text starts with
+//   AhoCorasick  every occurrence of every pattern in one pass over the text
+// pattern ids are the order the patterns went in, a repeated pattern is
+// only ever reported under the id of its first copy
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    // sorted by symbol
+    children: Vec<(T, usize)>,
+    pattern: Option<usize>,
+    depth: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trie<T> {
+    nodes: Vec<Node<T>>,
+    inserted: usize,
+}
+
+impl<T: Copy + Ord> Default for Trie<T> {
+    fn default() -> Self {
+        Trie {
+            nodes: vec![Node {
+                children: Vec::new(),
+                pattern: None,
+                depth: 0,
+            }],
+            inserted: 0,
+        }
+    }
+}
+
+impl<T: Copy + Ord> Trie<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // returns the id of the pattern
+    pub fn insert(&mut self, pattern: impl IntoIterator<Item = T>) -> usize {
+        let mut node = 0;
+        for symbol in pattern {
+            node = match self.search(node, symbol) {
+                Ok(i) => self.nodes[node].children[i].1,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node {
+                        children: Vec::new(),
+                        pattern: None,
+                        depth: self.nodes[node].depth + 1,
+                    });
+                    self.nodes[node].children.insert(i, (symbol, child));
+                    child
+                }
+            };
+        }
+
+        let id = self.inserted;
+        self.inserted += 1;
+        self.nodes[node].pattern.get_or_insert(id);
+        id
+    }
+
+    // patterns inserted, repeats included
+    pub fn len(&self) -> usize {
+        self.inserted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserted == 0
+    }
+
+    // id of the pattern equal to `text`
+    pub fn get(&self, text: impl IntoIterator<Item = T>) -> Option<usize> {
+        let mut node = 0;
+        for symbol in text {
+            node = self.child(node, symbol)?;
+        }
+        self.nodes[node].pattern
+    }
+
+    // the patterns `text` starts with as (id, length), shortest first
+    pub fn prefixes<'a, I>(&'a self, text: I) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: 'a,
+    {
+        let mut text = text.into_iter();
+        let mut node = Some(0);
+        std::iter::from_fn(move || {
+            loop {
+                let current = &self.nodes[node?];
+                node = text
+                    .next()
+                    .and_then(|symbol| self.child_of(current, symbol));
+                if let Some(id) = current.pattern {
+                    return Some((id, current.depth));
+                }
+            }
+        })
+    }
+
+    fn child(&self, node: usize, symbol: T) -> Option<usize> {
+        self.child_of(&self.nodes[node], symbol)
+    }
+
+    fn child_of(&self, node: &Node<T>, symbol: T) -> Option<usize> {
+        node.children
+            .binary_search_by(|&(s, _)| s.cmp(&symbol))
+            .ok()
+            .map(|i| node.children[i].1)
+    }
+
+    fn search(&self, node: usize, symbol: T) -> Result<usize, usize> {
+        self.nodes[node]
+            .children
+            .binary_search_by(|&(s, _)| s.cmp(&symbol))
+    }
+}
+
+impl<T: Copy + Ord, P: IntoIterator<Item = T>> FromIterator<P> for Trie<T> {
+    fn from_iter<I: IntoIterator<Item = P>>(patterns: I) -> Self {
+        let mut trie = Trie::new();
+        for pattern in patterns {
+            trie.insert(pattern);
+        }
+        trie
+    }
+}
+
+// one occurrence of a pattern, covering text[start..end]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick<T> {
+    trie: Trie<T>,
+    // longest proper suffix of a node that is also in the trie
+    fail: Vec<usize>,
+    // longest proper suffix of a node that ends a pattern
+    output: Vec<Option<usize>>,
+}
+
+impl<T: Copy + Ord> AhoCorasick<T> {
+    pub fn new<P: IntoIterator<Item = T>>(patterns: impl IntoIterator<Item = P>) -> Self {
+        let trie: Trie<T> = patterns.into_iter().collect();
+        assert!(
+            trie.nodes[0].pattern.is_none(),
+            "an empty pattern would match everywhere"
+        );
+
+        let mut fail = vec![0; trie.nodes.len()];
+        let mut output = vec![None; trie.nodes.len()];
+
+        // breadth first so a node's fail link is always done before its children need it
+        let mut queue: VecDeque<usize> = trie.nodes[0].children.iter().map(|&(_, c)| c).collect();
+        while let Some(node) = queue.pop_front() {
+            for &(symbol, child) in &trie.nodes[node].children {
+                let mut f = fail[node];
+                let target = loop {
+                    if let Some(next) = trie.child(f, symbol) {
+                        break next;
+                    }
+                    if f == 0 {
+                        break 0;
+                    }
+                    f = fail[f];
+                };
+                fail[child] = target;
+                output[child] = match trie.nodes[target].pattern {
+                    Some(_) => Some(target),
+                    None => output[target],
+                };
+                queue.push_back(child);
+            }
+        }
+
+        AhoCorasick { trie, fail, output }
+    }
+
+    pub fn patterns(&self) -> &Trie<T> {
+        &self.trie
+    }
+
+    // every occurrence of every pattern, overlapping ones included
+    // ordered by where they end, the longest first among those ending at the same place
+    pub fn find_iter<'a, I>(&'a self, text: I) -> impl Iterator<Item = Match> + 'a
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: 'a,
+    {
+        let mut text = text.into_iter();
+        let (mut node, mut end) = (0, 0);
+        let mut report: Option<usize> = None;
+        std::iter::from_fn(move || {
+            loop {
+                if let Some(at) = report {
+                    report = self.output[at];
+                    let found = &self.trie.nodes[at];
+                    return Some(Match {
+                        pattern: found.pattern.expect("output links only point at patterns"),
+                        start: end - found.depth,
+                        end,
+                    });
+                }
+
+                node = self.step(node, text.next()?);
+                end += 1;
+                report = match self.trie.nodes[node].pattern {
+                    Some(_) => Some(node),
+                    None => self.output[node],
+                };
+            }
+        })
+    }
+
+    pub fn is_match(&self, text: impl IntoIterator<Item = T>) -> bool {
+        self.find_iter(text).next().is_some()
+    }
+
+    fn step(&self, mut node: usize, symbol: T) -> usize {
+        loop {
+            if let Some(next) = self.trie.child(node, symbol) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.fail[node];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn naive<T: PartialEq>(patterns: &[Vec<T>], text: &[T]) -> Vec<Match> {
+        let mut found = Vec::new();
+        for end in 1..=text.len() {
+            let mut here: Vec<Match> = Vec::new();
+            for (id, pattern) in patterns.iter().enumerate() {
+                let n = pattern.len();
+                if n <= end
+                    && text[end - n..end] == pattern[..]
+                    && !here.iter().any(|m| m.start == end - n)
+                {
+                    here.push(Match {
+                        pattern: id,
+                        start: end - n,
+                        end,
+                    });
+                }
+            }
+            here.sort_by_key(|m| m.start);
+            found.extend(here);
+        }
+        found
+    }
+
+    #[test]
+    fn trie_prefixes() {
+        let trie: Trie<u8> = ["r", "wr", "b", "g", "bwu", "rb", "gb", "br", "b"]
+            .iter()
+            .map(|p| p.bytes())
+            .collect();
+        assert_eq!(trie.len(), 9);
+        assert_eq!(trie.get("bwu".bytes()), Some(4));
+        assert_eq!(trie.get("bw".bytes()), None);
+        // the second "b" keeps the first id
+        assert_eq!(trie.get("b".bytes()), Some(2));
+        assert_eq!(
+            trie.prefixes("bwurrg".bytes()).collect::<Vec<_>>(),
+            vec![(2, 1), (4, 3)]
+        );
+        assert_eq!(trie.prefixes("x".bytes()).count(), 0);
+    }
+
+    #[test]
+    fn classic_example() {
+        let ac = AhoCorasick::new(["he", "she", "his", "hers"].iter().map(|p| p.bytes()));
+        let found: Vec<(usize, usize, usize)> = ac
+            .find_iter("ushers".bytes())
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect();
+        assert_eq!(found, vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+        assert!(!ac.is_match("xyz".bytes()));
+    }
+
+    #[test]
+    fn chars_count_positions_in_chars() {
+        let ac = AhoCorasick::new(["é", "aé", "éé"].iter().map(|p| p.chars()));
+        let found: Vec<Match> = ac.find_iter("aééx".chars()).collect();
+        assert_eq!(
+            found,
+            vec![
+                Match {
+                    pattern: 1,
+                    start: 0,
+                    end: 2
+                },
+                Match {
+                    pattern: 0,
+                    start: 1,
+                    end: 2
+                },
+                Match {
+                    pattern: 2,
+                    start: 1,
+                    end: 3
+                },
+                Match {
+                    pattern: 0,
+                    start: 2,
+                    end: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_naive_search() {
+        let mut rng = Rng::new(48);
+        let alphabet = ['a', 'b', 'c', 'é'];
+        let pick = |rng: &mut Rng, max: u64| -> Vec<char> {
+            (0..=rng.below(max))
+                .map(|_| alphabet[rng.below(alphabet.len() as u64) as usize])
+                .collect()
+        };
+
+        for _ in 0..200 {
+            let patterns: Vec<Vec<char>> = (0..=rng.below(8)).map(|_| pick(&mut rng, 4)).collect();
+            let text = pick(&mut rng, 60);
+            let ac = AhoCorasick::new(patterns.iter().map(|p| p.iter().copied()));
+            let found: Vec<Match> = ac.find_iter(text.iter().copied()).collect();
+            assert_eq!(
+                found,
+                naive(&patterns, &text),
+                "{:?} in {:?}",
+                patterns,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+
+// arithmetic for the counting paths, where answers can outgrow 64 bits
+// by default the ops are plain + and *, same as writing them out (debug panics, release wraps)
+//   --features checked  every op is checked, inside `checked` an overflow comes back as an
+//                       error naming the operands, anywhere else it panics with the same message
+//   --features wide     Count and Signed are 128 bit so the answers stay correct
+// both together gives checked 128 bit maths
+// days forward the features with `checked = ["common/checked"]` and `wide = ["common/wide"]`
+
+#[cfg(not(feature = "wide"))]
+pub type Count = u64;
+#[cfg(feature = "wide")]
+pub type Count = u128;
+
+#[cfg(not(feature = "wide"))]
+pub type Signed = i64;
+#[cfg(feature = "wide")]
+pub type Signed = i128;
+
+pub trait Arith: Copy + Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn plus(self, rhs: Self) -> Self;
+    fn minus(self, rhs: Self) -> Self;
+    fn times(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_arith {
+    ($($t:ty),*) => {$(
+        impl Arith for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn plus(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_add(rhs).unwrap_or_else(|| overflow(self, '+', rhs, self.wrapping_add(rhs)))
+                } else {
+                    self + rhs
+                }
+            }
+
+            fn minus(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_sub(rhs).unwrap_or_else(|| overflow(self, '-', rhs, self.wrapping_sub(rhs)))
+                } else {
+                    self - rhs
+                }
+            }
+
+            fn times(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_mul(rhs).unwrap_or_else(|| overflow(self, '*', rhs, self.wrapping_mul(rhs)))
+                } else {
+                    self * rhs
+                }
+            }
+        }
+    )*};
+}
+
+impl_arith!(u64, u128, i64, i128, usize);
+
+pub fn sum<T: Arith>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::ZERO, T::plus)
+}
+
+pub fn product<T: Arith>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::ONE, T::times)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow(String);
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+thread_local! {
+    // the first overflow on this thread inside `checked`,
+    // the outer None is outside it where an overflow panics
+    static WATCH: RefCell<Option<Option<Overflow>>> = const { RefCell::new(None) };
+}
+
+// runs `solve` and hands back the first overflow it hit instead of the answer
+// nothing unwinds, the ops carry on with wrapped values until `solve` returns
+// so days wrap their whole solve in it: `num::checked(|| solve_part1(&input))`
+pub fn checked<T>(solve: impl FnOnce() -> T) -> Result<T, Overflow> {
+    match watched(true, solve) {
+        (answer, None) => Ok(answer),
+        (_, Some(overflow)) => Err(overflow),
+    }
+}
+
+// `checked` for main, an overflow goes to stderr and fails the run without a panic
+pub fn checked_or_exit<T>(solve: impl FnOnce() -> T) -> T {
+    checked(solve).unwrap_or_else(|overflow| {
+        eprintln!("{}", overflow);
+        std::process::exit(1)
+    })
+}
+
+// for common::par, whose workers run on threads of their own
+pub(crate) fn watching() -> bool {
+    WATCH.with_borrow(Option::is_some)
+}
+
+// `work` with its overflows caught like `checked` does if `watch`, else left to panic,
+// its result comes back either way
+pub(crate) fn watched<T>(watch: bool, work: impl FnOnce() -> T) -> (T, Option<Overflow>) {
+    if !watch {
+        return (work(), None);
+    }
+    let outer = WATCH.replace(Some(None));
+    let result = work();
+    (result, WATCH.replace(outer).flatten())
+}
+
+// an overflow on this thread, kept if its the first inside `checked`
+pub(crate) fn record(overflow: Overflow) {
+    WATCH.with_borrow_mut(|watch| match watch {
+        Some(first) => {
+            first.get_or_insert(overflow);
+        }
+        None => panic!("{}", overflow),
+    });
+}
+
+fn overflow<T: Display>(lhs: T, op: char, rhs: T, wrapped: T) -> T {
+    record(Overflow(format!(
+        "arithmetic overflow: {} {} {} does not fit in {} (try --features wide)",
+        lhs,
+        op,
+        rhs,
+        std::any::type_name::<T>()
+    )));
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_and_products() {
+        assert_eq!(sum([1u64, 2, 3]), 6);
+        assert_eq!(product([2i64, -3, 4]), -24);
+        assert_eq!(sum(Vec::<Count>::new()), 0);
+        assert_eq!(product(Vec::<Signed>::new()), 1);
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn wide_counts_past_64_bits() {
+        let big = Count::from(u64::MAX);
+        assert_eq!(big.times(4).plus(4), (big + 1) * 4);
+        assert_eq!(Signed::from(i64::MIN).minus(1), -(1i128 << 63) - 1);
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    #[should_panic(expected = "arithmetic overflow: 18446744073709551615 + 1")]
+    fn checked_reports_overflow() {
+        u64::MAX.plus(1);
+    }
+
+    #[test]
+    fn checked_without_an_overflow() {
+        assert_eq!(checked(|| 6u64.times(7)), Ok(42));
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn checked_hands_back_the_first_overflow() {
+        let result = checked(|| u64::MAX.plus(2).minus(3));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "arithmetic overflow: 18446744073709551615 + 2 does not fit in u64 (try --features wide)"
+        );
+        // and stops watching once its done
+        assert!(!watching());
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    #[should_panic(expected = "does not fit in i64")]
+    fn checked_reports_signed_overflow() {
+        (i64::MAX / 2).times(3);
+    }
+}
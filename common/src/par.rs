@@ -0,0 +1,118 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::num;
+
+// spreads independent pieces of work over a pool of scoped threads
+// without `--features parallel` everything runs on the calling thread,
+// days forward the feature with `parallel = ["common/parallel"]`
+// results always come back in input order, so sums and first-best picks
+// give the same answer either way
+
+// how many threads `map` uses, 1 unless the parallel feature is on
+pub fn threads() -> usize {
+    if cfg!(feature = "parallel") {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        1
+    }
+}
+
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    map_with(threads(), items, f)
+}
+
+// same as `map` with an explicit thread count
+// workers take the next item off a shared counter, so a few slow items
+// (big ranges, long loops) don't leave the other threads idle
+pub fn map_with<T: Sync, R: Send>(
+    threads: usize,
+    items: &[T],
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let threads = threads.min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    // inside `num::checked` the workers catch their overflows too and hand them back here
+    let watch = num::watching();
+    let overflows = Mutex::new(Vec::new());
+
+    let next = AtomicUsize::new(0);
+    let done = Mutex::new(Vec::with_capacity(items.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let (local, overflow) = num::watched(watch, || {
+                    let mut local = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else { break };
+                        local.push((i, f(item)));
+                    }
+                    local
+                });
+                done.lock().unwrap().extend(local);
+                overflows.lock().unwrap().extend(overflow);
+            });
+        }
+    });
+    for overflow in overflows.into_inner().unwrap() {
+        num::record(overflow);
+    }
+
+    let mut done = done.into_inner().unwrap();
+    done.sort_unstable_by_key(|&(i, _)| i);
+    done.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_thread_count_matches_sequential() {
+        let items: Vec<u64> = (0..1_000).collect();
+        let square = |&x: &u64| x * x;
+        let expected: Vec<u64> = items.iter().map(square).collect();
+        for threads in 1..=8 {
+            assert_eq!(
+                map_with(threads, &items, square),
+                expected,
+                "{} threads",
+                threads
+            );
+        }
+        assert_eq!(map(&items, square), expected);
+    }
+
+    #[test]
+    fn uneven_work_keeps_input_order() {
+        // a few items much slower than the rest
+        let items: Vec<u64> = (0..64)
+            .map(|i| if i % 16 == 0 { 200_000 } else { 10 })
+            .collect();
+        let work = |&n: &u64| (0..n).fold(0u64, |acc, x| acc.wrapping_mul(31).wrapping_add(x));
+        let expected: Vec<u64> = items.iter().map(work).collect();
+        assert_eq!(map_with(4, &items, work), expected);
+        assert!(map_with(4, &[] as &[u64], work).is_empty());
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn overflow_on_a_worker_comes_back_to_checked() {
+        use crate::num::Arith;
+
+        let items: Vec<u64> = (0..100).collect();
+        let result =
+            num::checked(|| map_with(4, &items, |&x| if x == 57 { u64::MAX.plus(x) } else { x }));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("18446744073709551615 + 57")
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+
+use flate2::Compression;
+use flate2::Crc;
+use flate2::write::ZlibEncoder;
+
+// turns grid states into pictures for eyeballing a solution
+// a scene is one colour per cell plus overlays drawn on top, and can be written
+// out as png, ppm or svg with every cell blown up to `scale` pixels
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    // well spread colours for labelling things by index, golden ratio steps round the hue wheel
+    pub fn distinct(index: usize) -> Rgb {
+        let hue = (index as f64 * 0.618_033_988_75).fract() * 6.0;
+        let x = 1.0 - (hue % 2.0 - 1.0).abs();
+        let (r, g, b) = match hue as u32 {
+            0 => (1.0, x, 0.0),
+            1 => (x, 1.0, 0.0),
+            2 => (0.0, 1.0, x),
+            3 => (0.0, x, 1.0),
+            4 => (x, 0.0, 1.0),
+            _ => (1.0, 0.0, x),
+        };
+        // keep it a bit pastel so overlays stand out
+        let channel = |v: f64| (70.0 + v * 170.0) as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// char -> colour, anything not set explicitly gets a distinct colour from its code point
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Palette::default()
+    }
+
+    pub fn with(mut self, ch: char, colour: Rgb) -> Self {
+        self.colours.insert(ch, colour);
+        self
+    }
+
+    pub fn colour(&self, ch: char) -> Rgb {
+        self.colours
+            .get(&ch)
+            .copied()
+            .unwrap_or_else(|| Rgb::distinct(ch as usize))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Overlay {
+    // cells drawn as a smaller square inside the cell
+    Marks(Vec<(usize, usize)>, Rgb),
+    // a line through the centres of the cells in order
+    Path(Vec<(usize, usize)>, Rgb),
+}
+
+#[derive(Debug, Clone)]
+pub struct Scene {
+    width: usize,
+    height: usize,
+    cells: Vec<Rgb>,
+    overlays: Vec<Overlay>,
+}
+
+impl Scene {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Scene {
+            width,
+            height,
+            cells: vec![background; width * height],
+            overlays: Vec::new(),
+        }
+    }
+
+    // one row per line, short lines are padded with the colour of ' '
+    pub fn from_chars(input: &str, palette: &Palette) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut scene = Scene::new(width, lines.len(), palette.colour(' '));
+
+        for (r, line) in lines.iter().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                scene.fill(r, c, palette.colour(ch));
+            }
+        }
+        scene
+    }
+
+    pub fn fill(&mut self, r: usize, c: usize, colour: Rgb) {
+        assert!(
+            r < self.height && c < self.width,
+            "({}, {}) is outside the scene",
+            r,
+            c
+        );
+        self.cells[r * self.width + c] = colour;
+    }
+
+    pub fn mark(&mut self, cells: impl IntoIterator<Item = (usize, usize)>, colour: Rgb) {
+        self.overlays
+            .push(Overlay::Marks(cells.into_iter().collect(), colour));
+    }
+
+    pub fn path(&mut self, cells: impl IntoIterator<Item = (usize, usize)>, colour: Rgb) {
+        self.overlays
+            .push(Overlay::Path(cells.into_iter().collect(), colour));
+    }
+
+    // picks the format from the extension, the path comes off the command line
+    // so an unknown one is an InvalidInput error for the day to report
+    pub fn save(&self, path: &str, scale: usize) -> io::Result<()> {
+        let bytes = match path.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("png") => self.to_png(scale),
+            Some("ppm") => self.to_ppm(scale),
+            Some("svg") => self.to_svg(scale).into_bytes(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown image format for {}, use .png, .ppm or .svg", path),
+                ));
+            }
+        };
+        fs::write(path, bytes)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", path, e)))
+    }
+
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height, pixels) = self.rasterize(scale);
+        let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for Rgb(r, g, b) in pixels {
+            out.extend([r, g, b]);
+        }
+        out
+    }
+
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let (width, height, pixels) = self.rasterize(scale);
+
+        // every scanline starts with filter type 0 (none)
+        let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+        for row in pixels.chunks(width.max(1)) {
+            raw.push(0);
+            for &Rgb(r, g, b) in row {
+                raw.extend([r, g, b]);
+            }
+        }
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw).unwrap();
+        let data = encoder.finish().unwrap();
+
+        let mut header = Vec::new();
+        header.extend((width as u32).to_be_bytes());
+        header.extend((height as u32).to_be_bytes());
+        // 8 bit depth, truecolour, default compression, filter and no interlace
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &data);
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    pub fn to_svg(&self, scale: usize) -> String {
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+            self.width * scale,
+            self.height * scale
+        );
+
+        // merge runs of the same colour along each row to keep the file small
+        for (r, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            let mut c = 0;
+            while c < row.len() {
+                let run = row[c..].iter().take_while(|&&x| x == row[c]).count();
+                out += &format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    c * scale,
+                    r * scale,
+                    run * scale,
+                    scale,
+                    row[c].hex()
+                );
+                c += run;
+            }
+        }
+
+        let inset = mark_inset(scale);
+        let centre = |cell: usize| cell as f64 * scale as f64 + scale as f64 / 2.0;
+        for overlay in &self.overlays {
+            match overlay {
+                Overlay::Marks(cells, colour) => {
+                    for &(r, c) in cells {
+                        out += &format!(
+                            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                            c * scale + inset,
+                            r * scale + inset,
+                            scale - 2 * inset,
+                            scale - 2 * inset,
+                            colour.hex()
+                        );
+                    }
+                }
+                Overlay::Path(cells, colour) => {
+                    let points: Vec<String> = cells
+                        .iter()
+                        .map(|&(r, c)| format!("{},{}", centre(c), centre(r)))
+                        .collect();
+                    out += &format!(
+                        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n",
+                        points.join(" "),
+                        colour.hex(),
+                        line_thickness(scale)
+                    );
+                }
+            }
+        }
+
+        out += "</svg>\n";
+        out
+    }
+
+    // (width, height, pixels row by row) with the overlays burned in
+    fn rasterize(&self, scale: usize) -> (usize, usize, Vec<Rgb>) {
+        assert!(scale > 0, "scale must be at least 1");
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut pixels = vec![Rgb::BLACK; width * height];
+
+        let mut fill_rect = |x: usize, y: usize, w: usize, h: usize, colour: Rgb| {
+            for py in y..(y + h).min(height) {
+                for px in x..(x + w).min(width) {
+                    pixels[py * width + px] = colour;
+                }
+            }
+        };
+
+        for (i, &colour) in self.cells.iter().enumerate() {
+            let (r, c) = (i / self.width, i % self.width);
+            fill_rect(c * scale, r * scale, scale, scale, colour);
+        }
+
+        let inset = mark_inset(scale);
+        let thickness = line_thickness(scale);
+        for overlay in &self.overlays {
+            match overlay {
+                Overlay::Marks(cells, colour) => {
+                    for &(r, c) in cells {
+                        let size = scale - 2 * inset;
+                        fill_rect(c * scale + inset, r * scale + inset, size, size, *colour);
+                    }
+                }
+                Overlay::Path(cells, colour) => {
+                    // step pixel by pixel between cell centres, stamping a square brush
+                    let half = scale / 2;
+                    let corner = |p: usize| (p * scale + half).saturating_sub(thickness / 2);
+                    // a single cell path is still drawn, as a dot
+                    let segments: Vec<[(usize, usize); 2]> = match cells.as_slice() {
+                        [only] => vec![[*only; 2]],
+                        _ => cells.windows(2).map(|w| [w[0], w[1]]).collect(),
+                    };
+                    for [from, to] in segments {
+                        let (r0, c0) = (corner(from.0) as f64, corner(from.1) as f64);
+                        let (r1, c1) = (corner(to.0) as f64, corner(to.1) as f64);
+                        let steps = (r1 - r0).abs().max((c1 - c0).abs()).max(1.0) as usize;
+                        for s in 0..=steps {
+                            let t = s as f64 / steps as f64;
+                            let y = (r0 + (r1 - r0) * t).round() as usize;
+                            let x = (c0 + (c1 - c0) * t).round() as usize;
+                            fill_rect(x, y, thickness, thickness, *colour);
+                        }
+                    }
+                }
+            }
+        }
+
+        (width, height, pixels)
+    }
+}
+
+// `--render <file>` on the command line, for days that can draw themselves
+pub fn requested() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    let at = args.iter().position(|a| a == "--render")?;
+    Some(
+        args.get(at + 1)
+            .expect("Usage: --render <file.png|file.ppm|file.svg>")
+            .clone(),
+    )
+}
+
+fn mark_inset(scale: usize) -> usize {
+    scale / 5
+}
+
+fn line_thickness(scale: usize) -> usize {
+    (scale / 3).max(1)
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    out.extend(crc.sum().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+    use std::path::Path;
+
+    fn checkerboard() -> Scene {
+        let palette = Palette::new().with('#', Rgb::BLACK).with('.', Rgb::WHITE);
+        Scene::from_chars("#.\n.#\n", &palette)
+    }
+
+    #[test]
+    fn save_picks_the_format_or_says_no() {
+        let dir = env::temp_dir();
+        let ppm = dir.join(format!("render-{}.ppm", std::process::id()));
+        let ppm = ppm.to_str().unwrap();
+        checkerboard().save(ppm, 2).unwrap();
+        assert_eq!(fs::read(ppm).unwrap(), checkerboard().to_ppm(2));
+        fs::remove_file(ppm).ok();
+
+        let gif = dir.join(format!("render-{}.gif", std::process::id()));
+        let gif = gif.to_str().unwrap();
+        let error = checkerboard().save(gif, 2).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!Path::new(gif).exists());
+        assert!(checkerboard().save("no-extension", 2).is_err());
+    }
+
+    #[test]
+    fn ppm_scales_cells() {
+        let ppm = checkerboard().to_ppm(2);
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+
+        let pixels: Vec<&[u8]> = ppm[header.len()..].chunks(3).collect();
+        assert_eq!(pixels.len(), 16);
+        // top row: two black pixels then two white
+        assert_eq!(pixels[0], [0, 0, 0]);
+        assert_eq!(pixels[1], [0, 0, 0]);
+        assert_eq!(pixels[2], [255, 255, 255]);
+        assert_eq!(pixels[15], [0, 0, 0]);
+    }
+
+    #[test]
+    fn png_round_trips_pixels() {
+        let mut scene = checkerboard();
+        scene.mark([(0, 1)], Rgb(255, 0, 0));
+        let png = scene.to_png(5);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 10);
+
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut raw)
+            .unwrap();
+
+        // 10 rows of filter byte plus 10 rgb pixels, and it should match the ppm
+        assert_eq!(raw.len(), 10 * 31);
+        let ppm = scene.to_ppm(5);
+        let body: Vec<u8> = raw.chunks(31).flat_map(|row| row[1..].to_vec()).collect();
+        assert_eq!(body, ppm[ppm.len() - 300..]);
+        // the mark sits inside the white cell with a white border round it
+        assert_eq!(body[(2 * 10 + 7) * 3..][..3], [255, 0, 0]);
+        assert_eq!(body[7 * 3..][..3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn svg_merges_runs_and_draws_paths() {
+        let mut scene = Scene::from_chars("aaab\n", &Palette::new());
+        scene.path([(0, 0), (0, 3)], Rgb(1, 2, 3));
+        let svg = scene.to_svg(10);
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert!(svg.contains("width=\"30\""));
+        assert!(svg.contains("<polyline points=\"5,5 35,5\""));
+        assert!(svg.contains("stroke=\"#010203\""));
+    }
+}
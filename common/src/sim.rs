@@ -0,0 +1,391 @@
+use std::env;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// step loops that can be watched in the terminal
+// a day implements `Simulation` for its state and hands it to `play`,
+// which draws a frame per step with play/pause, single step and speed controls
+
+pub trait Simulation {
+    // advances one step, false once there is nothing left to do
+    fn step(&mut self) -> bool;
+    fn render(&self) -> Frame;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Colour {
+    fn ansi(self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+        }
+    }
+}
+
+// a char grid where any cell can be highlighted, plus a status line underneath
+#[derive(Debug, Clone, Default)]
+pub struct Frame {
+    rows: Vec<Vec<(char, Option<Colour>)>>,
+    status: String,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, fill: char) -> Self {
+        Frame {
+            rows: vec![vec![(fill, None); width]; height],
+            status: String::new(),
+        }
+    }
+
+    pub fn from_chars(input: &str) -> Self {
+        Frame {
+            rows: input
+                .lines()
+                .map(|line| line.chars().map(|ch| (ch, None)).collect())
+                .collect(),
+            status: String::new(),
+        }
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, ch: char, colour: Option<Colour>) {
+        self.rows[r][c] = (ch, colour);
+    }
+
+    // writes `text` from (r, c) to the right, whatever doesnt fit is cut off
+    pub fn write(&mut self, r: usize, c: usize, text: &str, colour: Option<Colour>) {
+        let Some(row) = self.rows.get_mut(r) else {
+            return;
+        };
+        for (cell, ch) in row.iter_mut().skip(c).zip(text.chars()) {
+            *cell = (ch, colour);
+        }
+    }
+
+    // highlights a cell and keeps its char
+    pub fn paint(&mut self, r: usize, c: usize, colour: Colour) {
+        self.rows[r][c].1 = Some(colour);
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            out.extend(row.iter().map(|&(ch, _)| ch));
+            out.push('\n');
+        }
+        out + &self.status
+    }
+
+    // only emits escape codes when the colour changes, big frames draw a lot faster
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let mut current = None;
+            for &(ch, colour) in row {
+                if colour != current {
+                    match colour {
+                        Some(colour) => out += &format!("\x1b[1;{}m", colour.ansi()),
+                        None => out += "\x1b[0m",
+                    }
+                    current = colour;
+                }
+                out.push(ch);
+            }
+            if current.is_some() {
+                out += "\x1b[0m";
+            }
+            // clear whatever was left over from a wider previous frame
+            out += "\x1b[K\n";
+        }
+        out + &self.status + "\x1b[K"
+    }
+}
+
+// `--play` on the command line, for days that can be watched
+pub fn requested() -> bool {
+    env::args().any(|arg| arg == "--play")
+}
+
+// steps until the simulation finishes or `limit` steps have run, returns the step count
+pub fn run(sim: &mut impl Simulation, limit: usize) -> usize {
+    let mut steps = 0;
+    while steps < limit && sim.step() {
+        steps += 1;
+    }
+    steps
+}
+
+// interactive player, falls back to printing the final frame when not on a terminal
+//   space  play / pause
+//   n      single step (pauses)
+//   + -    faster / slower
+//   q      quit
+pub fn play(sim: &mut impl Simulation) {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        run(sim, usize::MAX);
+        println!("{}", sim.render().to_text());
+        return;
+    }
+
+    let _terminal = RawTerminal::enter();
+    let keys = spawn_key_reader();
+
+    let mut stdout = io::stdout().lock();
+    let mut speed = Speed::default();
+    let mut playing = true;
+    let mut finished = false;
+    let mut steps = 0;
+
+    write!(stdout, "\x1b[2J").unwrap();
+    loop {
+        let state = match (finished, playing) {
+            (true, _) => "finished",
+            (false, true) => "playing",
+            (false, false) => "paused",
+        };
+        write!(
+            stdout,
+            "\x1b[H{}\nstep {}  {}  {}  [space] play/pause  [n] step  [+/-] speed  [q] quit\x1b[K",
+            sim.render().to_ansi(),
+            steps,
+            state,
+            speed
+        )
+        .unwrap();
+        stdout.flush().unwrap();
+
+        // None means the frame delay ran out, a closed stdin counts as quitting
+        let key = if playing {
+            match keys.recv_timeout(speed.delay) {
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                key => Some(key.unwrap_or(b'q')),
+            }
+        } else {
+            Some(keys.recv().unwrap_or(b'q'))
+        };
+
+        let mut advance = 0;
+        match key {
+            Some(b' ') if !finished => playing = !playing,
+            Some(b'n') | Some(b'.') => {
+                playing = false;
+                advance = 1;
+            }
+            Some(b'+') | Some(b'=') => speed.faster(),
+            Some(b'-') | Some(b'_') => speed.slower(),
+            Some(b'q') => break,
+            None => advance = speed.steps_per_frame,
+            _ => {}
+        }
+
+        for _ in 0..advance {
+            if finished || !sim.step() {
+                finished = true;
+                playing = false;
+                break;
+            }
+            steps += 1;
+        }
+    }
+    writeln!(stdout).unwrap();
+}
+
+// a frame delay down to 10ms, after that it draws every nth step instead
+#[derive(Debug, Clone, Copy)]
+struct Speed {
+    delay: Duration,
+    steps_per_frame: usize,
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Speed {
+            delay: Duration::from_millis(80),
+            steps_per_frame: 1,
+        }
+    }
+}
+
+impl Speed {
+    const MIN_DELAY: Duration = Duration::from_millis(10);
+    const MAX_DELAY: Duration = Duration::from_millis(2560);
+
+    fn faster(&mut self) {
+        if self.delay > Speed::MIN_DELAY {
+            self.delay = (self.delay / 2).max(Speed::MIN_DELAY);
+        } else {
+            self.steps_per_frame = (self.steps_per_frame * 2).min(4096);
+        }
+    }
+
+    fn slower(&mut self) {
+        if self.steps_per_frame > 1 {
+            self.steps_per_frame /= 2;
+        } else {
+            self.delay = (self.delay * 2).min(Speed::MAX_DELAY);
+        }
+    }
+}
+
+impl std::fmt::Display for Speed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}ms", self.delay.as_millis())?;
+        if self.steps_per_frame > 1 {
+            write!(f, " x{}", self.steps_per_frame)?;
+        }
+        Ok(())
+    }
+}
+
+// keys arrive on a channel so the player can wait with a timeout
+pub fn spawn_key_reader() -> mpsc::Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0; 16];
+        while let Ok(read @ 1..) = stdin.read(&mut buffer) {
+            for &byte in &buffer[..read] {
+                if sender.send(byte).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+// unbuffered, unechoed input through stty, put back the way it was on drop
+pub struct RawTerminal {
+    saved: Option<String>,
+}
+
+impl RawTerminal {
+    pub fn enter() -> Self {
+        let saved = stty(&["-g"]).map(|s| s.trim().to_string());
+        stty(&["-icanon", "-echo"]);
+        print!("\x1b[?25l");
+        RawTerminal { saved }
+    }
+
+    // (rows, columns) of the terminal on stdin
+    pub fn size() -> Option<(usize, usize)> {
+        let size = stty(&["size"])?;
+        let (rows, cols) = size.trim().split_once(' ')?;
+        Some((rows.parse().ok()?, cols.parse().ok()?))
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h");
+        io::stdout().flush().ok();
+        match &self.saved {
+            Some(saved) => stty(&[saved.as_str()]),
+            None => stty(&["sane"]),
+        };
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // counts down and draws the counter as a row of stars
+    struct Countdown(usize);
+
+    impl Simulation for Countdown {
+        fn step(&mut self) -> bool {
+            if self.0 == 0 {
+                return false;
+            }
+            self.0 -= 1;
+            true
+        }
+
+        fn render(&self) -> Frame {
+            let mut frame = Frame::new(self.0.max(1), 1, '.');
+            for c in 0..self.0 {
+                frame.set(0, c, '*', Some(Colour::Green));
+            }
+            frame.set_status(format!("{} left", self.0));
+            frame
+        }
+    }
+
+    #[test]
+    fn run_stops_at_the_end_or_the_limit() {
+        assert_eq!(run(&mut Countdown(5), 3), 3);
+        let mut sim = Countdown(5);
+        assert_eq!(run(&mut sim, 100), 5);
+        assert_eq!(sim.render().to_text(), ".\n0 left");
+    }
+
+    #[test]
+    fn ansi_only_switches_colour_on_changes() {
+        let mut frame = Frame::from_chars("#..\n.#.\n");
+        frame.paint(0, 1, Colour::Red);
+        frame.paint(0, 2, Colour::Red);
+        frame.set(1, 2, '@', Some(Colour::Yellow));
+
+        assert_eq!(
+            frame.to_ansi(),
+            "#\x1b[1;31m..\x1b[0m\x1b[K\n.#\x1b[1;33m@\x1b[0m\x1b[K\n\x1b[K"
+        );
+        assert_eq!(frame.to_text(), "#..\n.#@\n");
+    }
+
+    #[test]
+    fn write_cuts_off_at_the_edge() {
+        let mut frame = Frame::new(5, 2, '.');
+        frame.write(0, 3, "abc", Some(Colour::Cyan));
+        frame.write(1, 0, "xy", None);
+        frame.write(2, 0, "off the bottom", None);
+        assert_eq!(frame.to_text(), "...ab\nxy...\n");
+    }
+
+    #[test]
+    fn speed_goes_to_multi_step_frames_at_the_bottom() {
+        let mut speed = Speed::default();
+        for _ in 0..5 {
+            speed.faster();
+        }
+        assert_eq!(speed.delay, Speed::MIN_DELAY);
+        assert_eq!(speed.steps_per_frame, 4);
+        assert_eq!(speed.to_string(), "10ms x4");
+        for _ in 0..3 {
+            speed.slower();
+        }
+        assert_eq!((speed.delay.as_millis(), speed.steps_per_frame), (20, 1));
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BinaryHeap;
+
+// k-d tree over integer points in K dimensions (2 for grids, 3 for the 3d days)
+// built once, then queried for the k nearest points or every point within a radius
+// points are referred to by their index in the input order
+// the tree is implicit: each slice of `nodes` keeps its median (on the axis for that
+// depth) in the middle with the smaller half before it and the larger half after
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    // squared, so distances stay exact integers, radii are squared too
+    Euclidean,
+    Manhattan,
+}
+
+impl Metric {
+    pub fn distance<const K: usize>(self, a: &[i64; K], b: &[i64; K]) -> i64 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| self.axis_distance(a - b))
+            .sum()
+    }
+
+    // the least a point on the far side of a split can be away, `d` from the split on one axis
+    fn axis_distance(self, d: i64) -> i64 {
+        match self {
+            Metric::Euclidean => d * d,
+            Metric::Manhattan => d.abs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KdTree<const K: usize> {
+    nodes: Vec<([i64; K], usize)>,
+}
+
+impl<const K: usize> KdTree<K> {
+    pub fn new(points: impl IntoIterator<Item = [i64; K]>) -> Self {
+        let mut nodes: Vec<([i64; K], usize)> = points
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+        build(&mut nodes, 0);
+        KdTree { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // the `k` points closest to `query` as (distance, index), closest first
+    // ties go to the lower index, and a point at `query` itself is included
+    pub fn nearest(&self, query: &[i64; K], k: usize, metric: Metric) -> Vec<(i64, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_in(&self.nodes, 0, query, k, metric, &mut best);
+        }
+        best.into_sorted_vec()
+    }
+
+    // every point at most `radius` from `query` as (distance, index), in no particular order
+    pub fn within(&self, query: &[i64; K], radius: i64, metric: Metric) -> Vec<(i64, usize)> {
+        let mut found = Vec::new();
+        self.within_in(&self.nodes, 0, query, radius, metric, &mut found);
+        found
+    }
+
+    fn nearest_in(
+        &self,
+        nodes: &[([i64; K], usize)],
+        depth: usize,
+        query: &[i64; K],
+        k: usize,
+        metric: Metric,
+        best: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let (point, index) = nodes[mid];
+        let axis = depth % K;
+
+        best.push((metric.distance(&point, query), index));
+        if best.len() > k {
+            best.pop();
+        }
+
+        // the side the query is on first, it's the one likely to tighten `best`
+        let (near, far) = if query[axis] < point[axis] {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        self.nearest_in(near, depth + 1, query, k, metric, best);
+
+        let bound = metric.axis_distance(query[axis] - point[axis]);
+        if best.len() < k || best.peek().is_some_and(|&(worst, _)| bound <= worst) {
+            self.nearest_in(far, depth + 1, query, k, metric, best);
+        }
+    }
+
+    fn within_in(
+        &self,
+        nodes: &[([i64; K], usize)],
+        depth: usize,
+        query: &[i64; K],
+        radius: i64,
+        metric: Metric,
+        found: &mut Vec<(i64, usize)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let (point, index) = nodes[mid];
+        let axis = depth % K;
+
+        let distance = metric.distance(&point, query);
+        if distance <= radius {
+            found.push((distance, index));
+        }
+
+        let d = query[axis] - point[axis];
+        if d <= 0 || metric.axis_distance(d) <= radius {
+            self.within_in(&nodes[..mid], depth + 1, query, radius, metric, found);
+        }
+        if d >= 0 || metric.axis_distance(d) <= radius {
+            self.within_in(&nodes[mid + 1..], depth + 1, query, radius, metric, found);
+        }
+    }
+}
+
+fn build<const K: usize>(nodes: &mut [([i64; K], usize)], depth: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+    let axis = depth % K;
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by_key(mid, |(p, _)| p[axis]);
+    let (left, right) = nodes.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_points<const K: usize>(rng: &mut Rng, n: usize, span: i64) -> Vec<[i64; K]> {
+        (0..n)
+            .map(|_| std::array::from_fn(|_| rng.range(-span, span)))
+            .collect()
+    }
+
+    fn brute_force<const K: usize>(
+        points: &[[i64; K]],
+        query: &[i64; K],
+        metric: Metric,
+    ) -> Vec<(i64, usize)> {
+        let mut all: Vec<(i64, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (metric.distance(p, query), i))
+            .collect();
+        all.sort_unstable();
+        all
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let mut rng = Rng::new(47);
+        for metric in [Metric::Euclidean, Metric::Manhattan] {
+            // small span so there are plenty of ties and duplicate points
+            let points: Vec<[i64; 3]> = random_points(&mut rng, 300, 12);
+            let tree = KdTree::new(points.iter().copied());
+            for _ in 0..50 {
+                let query = random_points::<3>(&mut rng, 1, 14)[0];
+                let expected = brute_force(&points, &query, metric);
+                for k in [1, 2, 7, 40, 300, 400] {
+                    assert_eq!(
+                        tree.nearest(&query, k, metric),
+                        expected[..k.min(points.len())],
+                        "{:?} k={} {:?}",
+                        metric,
+                        k,
+                        query
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn within_matches_brute_force() {
+        let mut rng = Rng::new(470);
+        for metric in [Metric::Euclidean, Metric::Manhattan] {
+            let points: Vec<[i64; 2]> = random_points(&mut rng, 500, 40);
+            let tree = KdTree::new(points.iter().copied());
+            for radius in [0, 1, 5, 20, 400] {
+                let query = random_points::<2>(&mut rng, 1, 40)[0];
+                let mut found = tree.within(&query, radius, metric);
+                found.sort_unstable();
+                let expected: Vec<(i64, usize)> = brute_force(&points, &query, metric)
+                    .into_iter()
+                    .filter(|&(d, _)| d <= radius)
+                    .collect();
+                assert_eq!(found, expected, "{:?} r={}", metric, radius);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_and_tiny_trees() {
+        let empty: KdTree<2> = KdTree::new([]);
+        assert!(empty.is_empty());
+        assert!(empty.nearest(&[0, 0], 3, Metric::Euclidean).is_empty());
+        assert!(empty.within(&[0, 0], 10, Metric::Manhattan).is_empty());
+
+        let tree = KdTree::new([[5, 5], [1, 1]]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.nearest(&[0, 0], 0, Metric::Manhattan), vec![]);
+        assert_eq!(
+            tree.nearest(&[0, 0], 5, Metric::Manhattan),
+            vec![(2, 1), (10, 0)]
+        );
+        assert_eq!(tree.within(&[4, 4], 2, Metric::Euclidean), vec![(2, 0)]);
+    }
+}
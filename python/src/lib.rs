@@ -0,0 +1,211 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use aoc::day::Day;
+use aoc::serve::Runner;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyLookupError, PyRuntimeError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+
+// python bindings for the solutions, for notebooks
+//   import aocpy
+//   aocpy.solve(2024, 16, 2, text)      same as aocpy.y2024_day16_part2(text)
+// answers come back as the string the day printed, a day that fails on its input
+// raises aocpy.InputError (a ValueError) with the day's own message
+// days run out of process like in the aoc tool and get built in release mode the first
+// time they're asked for, so the module only works from the checkout it was built in
+
+create_exception!(
+    aocpy,
+    InputError,
+    PyValueError,
+    "A day failed on the input it was given."
+);
+
+// one per python process so every day is only built once
+static RUNNER: LazyLock<Runner> = LazyLock::new(Runner::default);
+
+#[pymodule]
+mod aocpy {
+    use super::*;
+
+    #[pymodule_export]
+    use super::InputError;
+
+    // `timeout` is in seconds, None for no limit
+    #[pyfunction]
+    #[pyo3(signature = (year, day, part, input, timeout = None))]
+    fn solve(
+        py: Python<'_>,
+        year: u32,
+        day: u32,
+        part: u32,
+        input: &str,
+        timeout: Option<f64>,
+    ) -> PyResult<String> {
+        if !(1..=2).contains(&part) {
+            return Err(PyValueError::new_err(format!(
+                "part must be 1 or 2, not {}",
+                part
+            )));
+        }
+        let day = Day::new(year, day).map_err(PyLookupError::new_err)?;
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(format!("Bad timeout: {}", e)))?;
+
+        // the gil isnt needed while the day runs, other python threads can go on
+        py.detach(|| run(&day, part, input, timeout))
+    }
+
+    // (year, day) for every day there is a crate for, stubs included
+    #[pyfunction]
+    fn days() -> Vec<(u32, u32)> {
+        Day::all().iter().map(|d| (d.year, d.day)).collect()
+    }
+
+    // a y<year>_day<day>_part<n>(input, timeout=None) shortcut for every part of every day
+    #[pymodule_init]
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        let partial = m.py().import("functools")?.getattr("partial")?;
+        let solve = m.getattr("solve")?;
+        for day in Day::all() {
+            for part in 1..=2 {
+                let name = format!("y{}_day{}_part{}", day.year, day.day, part);
+                m.add(name, partial.call1((&solve, day.year, day.day, part))?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn run(day: &Day, part: u32, input: &str, timeout: Option<Duration>) -> PyResult<String> {
+    RUNNER.build(day).map_err(PyRuntimeError::new_err)?;
+    let run = RUNNER
+        .run(day, input, timeout)
+        .map_err(|e| InputError::new_err(rejection(&e)))?
+        .ok_or_else(|| {
+            PyTimeoutError::new_err(format!(
+                "{} took longer than {:.2?}",
+                day.name(),
+                timeout.unwrap_or_default()
+            ))
+        })?;
+    run.answer(part).map(str::to_string).ok_or_else(|| {
+        PyRuntimeError::new_err(format!(
+            "{} printed no answer for part {}",
+            day.name(),
+            part
+        ))
+    })
+}
+
+// "2024 day 16 exited with exit status: 101" and what the day said, without the
+// panic's source location and the backtrace (or the note about one), python users
+// have no use for those
+fn rejection(error: &str) -> String {
+    let mut lines = error.lines();
+    let first = lines.next().unwrap_or_default();
+    let message: Vec<&str> = lines
+        .skip_while(|line| !line.contains(" panicked at "))
+        .skip(1)
+        .take_while(|line| !line.starts_with("note: ") && *line != "stack backtrace:")
+        .collect();
+    if message.is_empty() {
+        error.trim_end().to_string()
+    } else {
+        format!("{}: {}", first, message.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyModule;
+
+    fn with_module(f: impl FnOnce(Python<'_>, &Bound<'_, PyModule>)) {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = pyo3::wrap_pymodule!(aocpy)(py);
+            f(py, module.bind(py));
+        });
+    }
+
+    #[test]
+    fn panics_read_as_their_message() {
+        let error = "2024 day 16 exited with exit status: 101\n\
+            thread 'main' panicked at src/main.rs:12:40:\n\
+            expected a maze with one S and one E\n\
+            note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+        assert_eq!(
+            rejection(error),
+            "2024 day 16 exited with exit status: 101: expected a maze with one S and one E"
+        );
+
+        let error = "2024 day 16 exited with exit status: 101\n\
+            thread 'main' panicked at src/main.rs:12:40:\n\
+            expected a maze\n\
+            stack backtrace:\n   0: __rustc::rust_begin_unwind\n";
+        assert_eq!(
+            rejection(error),
+            "2024 day 16 exited with exit status: 101: expected a maze"
+        );
+
+        // anything else comes through as is
+        let error = "2024 day 7 exited with exit status: 1\nPart 2 overflowed\n";
+        assert_eq!(rejection(error), error.trim_end());
+    }
+
+    #[test]
+    fn solves_from_python() {
+        with_module(|_, module| {
+            let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+            let solve = module.getattr("solve").unwrap();
+            let answer: String = solve
+                .call1((2024, 1, 1, example))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(answer, "11");
+
+            let shortcut = module.getattr("y2024_day1_part2").unwrap();
+            let answer: String = shortcut.call1((example,)).unwrap().extract().unwrap();
+            assert_eq!(answer, "31");
+
+            let days: Vec<(u32, u32)> = module
+                .getattr("days")
+                .unwrap()
+                .call0()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(days.contains(&(2024, 16)) && days.contains(&(2025, 1)));
+            assert!(module.hasattr("y2025_day8_part1").unwrap());
+        });
+    }
+
+    #[test]
+    fn bad_input_raises() {
+        with_module(|py, module| {
+            let solve = module.getattr("solve").unwrap();
+
+            let error = solve.call1((2024, 16, 1, "#\n")).unwrap_err();
+            assert!(error.is_instance_of::<InputError>(py));
+            assert!(error.is_instance_of::<PyValueError>(py));
+            let message = error.value(py).to_string();
+            assert!(
+                message.starts_with("2024 day 16 exited with"),
+                "{}",
+                message
+            );
+            assert!(!message.contains("RUST_BACKTRACE"), "{}", message);
+
+            let error = solve.call1((2019, 1, 1, "")).unwrap_err();
+            assert!(error.is_instance_of::<PyLookupError>(py));
+            let error = solve.call1((2024, 1, 3, "")).unwrap_err();
+            assert!(error.is_instance_of::<PyValueError>(py));
+            assert!(!error.is_instance_of::<InputError>(py));
+        });
+    }
+}